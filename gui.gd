@@ -24,9 +24,40 @@ func _init() -> void:
 	ControlUtil.h_expand_fill(le)
 	le.editable = false
 
-	AM.get_node(tr("HOTKEY_LISTENER_EXTENSION_NAME")).connect("key_received", self, "_on_key_received", [le])
+	var listener: Node = AM.get_node(tr("HOTKEY_LISTENER_EXTENSION_NAME"))
+	listener.set_key_events_enabled(true)
+	listener.connect("key_received", self, "_on_key_received", [le])
 
 	vb.add_child(le)
 
-func _on_key_received(text: String, le: LineEdit) -> void:
-	le.text = text
+	var recorded := LineEdit.new()
+	ControlUtil.h_expand_fill(recorded)
+	recorded.editable = false
+
+	var record := Button.new()
+	ControlUtil.h_expand_fill(record)
+	record.text = tr("HOTKEY_LISTENER_RECORD_BUTTON")
+	record.connect("pressed", self, "_on_record_pressed", [listener, recorded])
+
+	vb.add_child(record)
+	vb.add_child(recorded)
+
+func _on_key_received(key_name: String, details: Dictionary, le: LineEdit) -> void:
+	le.text = "%s %s" % [key_name, details.state]
+
+func _on_record_pressed(listener: Node, recorded: LineEdit) -> void:
+	var res: Result = Safely.wrap(
+		AM.em.load_gdnative_resource(
+			"HotkeyListener", "HotkeyListenerLib", "HotkeyListenerPopup"))
+	if res.is_err():
+		logger.error("Unable to create the hotkey popup")
+		return
+
+	var popup: WindowDialog = res.unwrap()
+	add_child(popup)
+	popup.capture_with(listener)
+	popup.connect("dialog_complete", self, "_on_dialog_complete", [listener, recorded])
+	popup.popup_centered_ratio(0.5)
+
+func _on_dialog_complete(keys: Array, listener: Node, recorded: LineEdit) -> void:
+	recorded.text = listener.format_hotkey([keys])
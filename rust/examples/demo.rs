@@ -2,7 +2,7 @@ use crossbeam_channel::unbounded;
 use hotkey_listener::hotkey_listener;
 
 fn main() {
-    let (s, r) = unbounded::<String>();
+    let (s, r) = unbounded::<hotkey_listener::ActionEvent>();
 
     let mut listener = hotkey_listener::HotkeyListener::new(s).unwrap();
 
@@ -30,16 +30,14 @@ fn main() {
             &["A".to_string(), "ShiftLeft".to_string()],
         )
         .unwrap();
-    listener.set_min_elapsed_time(0.2);
+    listener.set_min_elapsed_time(0.2).unwrap();
 
-    loop {
-        listener.poll();
+    // The listener polls itself on a worker thread, so events can be waited on directly
+    let _listener_thread = listener.spawn();
 
-        if r.is_empty() {
-            continue;
-        }
+    loop {
         match r.recv() {
-            Ok(s) => println!("{s}"),
+            Ok(e) => println!("{e:?}"),
             Err(e) => eprintln!("{e}"),
         }
     }
@@ -0,0 +1,80 @@
+use std::time::Instant;
+
+use crossbeam_channel::Sender;
+use livesplit_hotkey::KeyCode;
+
+use crate::hotkey_listener::Result;
+
+mod livesplit;
+pub use livesplit::LivesplitBackend;
+
+mod scripted;
+pub use scripted::ScriptedBackend;
+
+/// Whether a key went down or up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyState {
+    Pressed,
+    Released,
+}
+
+impl KeyState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyState::Pressed => "Pressed",
+            KeyState::Released => "Released",
+        }
+    }
+}
+
+/// A single key going down or up, as reported by a `Backend`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyEvent {
+    pub key: KeyCode,
+    pub state: KeyState,
+    /// When the backend observed the event.
+    pub time: Instant,
+}
+
+impl KeyEvent {
+    pub fn pressed(key: KeyCode) -> Self {
+        KeyEvent::pressed_at(key, Instant::now())
+    }
+
+    pub fn released(key: KeyCode) -> Self {
+        KeyEvent::released_at(key, Instant::now())
+    }
+
+    pub fn pressed_at(key: KeyCode, time: Instant) -> Self {
+        KeyEvent {
+            key,
+            state: KeyState::Pressed,
+            time,
+        }
+    }
+
+    pub fn released_at(key: KeyCode, time: Instant) -> Self {
+        KeyEvent {
+            key,
+            state: KeyState::Released,
+            time,
+        }
+    }
+}
+
+/// A source of key events for a `HotkeyListener`.
+///
+/// Backends are responsible for hooking individual keys and delivering a `KeyEvent` through the
+/// passed `Sender` every time a hooked key is pressed or released. Backends that cannot observe
+/// releases only ever send `KeyState::Pressed`.
+pub trait Backend: Send {
+    /// Starts delivering events for `key` through `sender`.
+    fn register(&mut self, key: KeyCode, sender: Sender<KeyEvent>) -> Result<()>;
+
+    /// Stops delivering events for `key`.
+    fn unregister(&mut self, key: KeyCode) -> Result<()>;
+
+    /// Whether the backend sends `KeyState::Released`. Features that depend on knowing which keys are
+    /// held, e.g. `ChordMode::Held`, are refused by the `HotkeyListener` if it does not.
+    fn reports_releases(&self) -> bool;
+}
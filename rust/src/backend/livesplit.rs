@@ -0,0 +1,47 @@
+use crossbeam_channel::Sender;
+use livesplit_hotkey::{Hook, KeyCode};
+
+use crate::backend::{Backend, KeyEvent};
+use crate::hotkey_listener::{Error, Result};
+
+/// Listens for key presses using an OS-level `livesplit_hotkey::Hook`.
+///
+/// The hook does not report key releases, so only `KeyState::Pressed` events are ever sent.
+pub struct LivesplitBackend {
+    hook: Hook,
+}
+
+impl LivesplitBackend {
+    /// Creates the underlying OS hook. This operation _can_ fail.
+    pub fn new() -> Result<Self> {
+        match Hook::new() {
+            Ok(hook) => Ok(LivesplitBackend { hook }),
+            Err(e) => Err(Error::HookCreate(e)),
+        }
+    }
+}
+
+impl Backend for LivesplitBackend {
+    fn register(&mut self, key: KeyCode, sender: Sender<KeyEvent>) -> Result<()> {
+        match self
+            .hook
+            .register(key, move || match sender.send(KeyEvent::pressed(key)) {
+                Ok(_) => {}
+                Err(e) => eprintln!("{e}"),
+            }) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(Error::CannotRegisterHotkey(key, e)),
+        }
+    }
+
+    fn unregister(&mut self, key: KeyCode) -> Result<()> {
+        match self.hook.unregister(key) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(Error::CannotUnregisterHotkey(key, e)),
+        }
+    }
+
+    fn reports_releases(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,112 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use crossbeam_channel::Sender;
+use livesplit_hotkey::KeyCode;
+
+use crate::backend::{Backend, KeyEvent};
+use crate::hotkey_listener::{Error, Result};
+
+/// An in-memory backend where key presses and releases are fed in manually.
+///
+/// Clones share the same state, so one clone can be handed to a `HotkeyListener` while another
+/// is used to script key presses. Useful for exercising a `HotkeyListener` without an OS hook.
+#[derive(Clone)]
+pub struct ScriptedBackend {
+    hooked: Arc<Mutex<HashMap<KeyCode, Sender<KeyEvent>>>>,
+    reports_releases: bool,
+}
+
+impl Default for ScriptedBackend {
+    fn default() -> Self {
+        ScriptedBackend {
+            hooked: Arc::new(Mutex::new(HashMap::new())),
+            reports_releases: true,
+        }
+    }
+}
+
+impl ScriptedBackend {
+    pub fn new() -> Self {
+        ScriptedBackend::default()
+    }
+
+    /// Creates a backend that claims not to report releases, like `LivesplitBackend`. Only presses should
+    /// be scripted on it.
+    pub fn without_releases() -> Self {
+        ScriptedBackend {
+            reports_releases: false,
+            ..ScriptedBackend::default()
+        }
+    }
+
+    /// Simulates a press of `key`. Returns `false` if the key is not currently hooked.
+    pub fn press(&self, key: KeyCode) -> bool {
+        self.send(KeyEvent::pressed(key))
+    }
+
+    /// Simulates a release of `key`. Returns `false` if the key is not currently hooked.
+    pub fn release(&self, key: KeyCode) -> bool {
+        self.send(KeyEvent::released(key))
+    }
+
+    /// Delivers an arbitrary event. Returns `false` if the event's key is not currently hooked.
+    pub fn send(&self, event: KeyEvent) -> bool {
+        match self
+            .hooked
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&event.key)
+        {
+            Some(sender) => match sender.send(event) {
+                Ok(_) => true,
+                Err(e) => {
+                    eprintln!("{e}");
+                    false
+                }
+            },
+            None => false,
+        }
+    }
+
+    /// Returns every key that is currently hooked.
+    ///
+    /// Keys are _not_ sorted.
+    pub fn hooked_keys(&self) -> Vec<KeyCode> {
+        self.hooked
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .keys()
+            .copied()
+            .collect()
+    }
+}
+
+impl Backend for ScriptedBackend {
+    fn register(&mut self, key: KeyCode, sender: Sender<KeyEvent>) -> Result<()> {
+        self.hooked
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key, sender);
+
+        Ok(())
+    }
+
+    fn unregister(&mut self, key: KeyCode) -> Result<()> {
+        match self
+            .hooked
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&key)
+        {
+            Some(_) => Ok(()),
+            None => Err(Error::KeyNotMapped(key)),
+        }
+    }
+
+    fn reports_releases(&self) -> bool {
+        self.reports_releases
+    }
+}
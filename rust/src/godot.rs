@@ -1,5 +1,7 @@
 use gdnative::prelude::*;
 
+use crate::hotkey_listener::Payload;
+
 mod node;
 use node::HotkeyListenerNode;
 
@@ -13,6 +15,125 @@ fn varray_to_vec(keys: &VariantArray) -> Vec<String> {
         .collect::<Vec<String>>()
 }
 
+/// Converts a `VariantArray` of `VariantArray`s to a `Vec` of `Vec`s.
+///
+/// Elements that are not arrays are converted to empty steps.
+fn varray_to_steps(steps: &VariantArray) -> Vec<Vec<String>> {
+    steps
+        .into_iter()
+        .map(|x| match x.to::<VariantArray>() {
+            Some(keys) => varray_to_vec(&keys),
+            None => vec![],
+        })
+        .collect::<Vec<Vec<String>>>()
+}
+
+/// Converts a `Variant` to a `Payload`.
+///
+/// Booleans, integers, floats, strings, node paths, arrays, pool arrays and dictionaries are converted,
+/// recursively. Dictionary keys are converted to strings. `Vector2`, `Vector3` and `Color` become arrays of
+/// floats. Returns an error naming the type of any other value, including a nested `Nil`.
+fn variant_to_payload(v: &Variant) -> Result<Payload, String> {
+    let unsupported = || format!("a `{:?}` cannot be used as a payload", v.get_type());
+
+    match v.get_type() {
+        VariantType::Bool => v.to::<bool>().map(Payload::Boolean).ok_or_else(unsupported),
+        VariantType::I64 => v.to::<i64>().map(Payload::Integer).ok_or_else(unsupported),
+        VariantType::F64 => v.to::<f64>().map(Payload::Float).ok_or_else(unsupported),
+        VariantType::GodotString | VariantType::NodePath => Ok(Payload::String(v.to_string())),
+        VariantType::Vector2 => match v.to::<Vector2>() {
+            Some(x) => Ok(floats_to_payload(&[x.x, x.y])),
+            None => Err(unsupported()),
+        },
+        VariantType::Vector3 => match v.to::<Vector3>() {
+            Some(x) => Ok(floats_to_payload(&[x.x, x.y, x.z])),
+            None => Err(unsupported()),
+        },
+        VariantType::Color => match v.to::<Color>() {
+            Some(x) => Ok(floats_to_payload(&[x.r, x.g, x.b, x.a])),
+            None => Err(unsupported()),
+        },
+        VariantType::VariantArray => match v.to::<VariantArray>() {
+            Some(a) => a
+                .iter()
+                .map(|x| variant_to_payload(&x))
+                .collect::<Result<Vec<Payload>, String>>()
+                .map(Payload::Array),
+            None => Err(unsupported()),
+        },
+        VariantType::ByteArray => match v.to::<PoolArray<u8>>() {
+            Some(a) => Ok(Payload::Array(
+                a.to_vec()
+                    .into_iter()
+                    .map(|x| Payload::Integer(x.into()))
+                    .collect(),
+            )),
+            None => Err(unsupported()),
+        },
+        VariantType::Int32Array => match v.to::<PoolArray<i32>>() {
+            Some(a) => Ok(Payload::Array(
+                a.to_vec()
+                    .into_iter()
+                    .map(|x| Payload::Integer(x.into()))
+                    .collect(),
+            )),
+            None => Err(unsupported()),
+        },
+        VariantType::Float32Array => match v.to::<PoolArray<f32>>() {
+            Some(a) => Ok(floats_to_payload(&a.to_vec())),
+            None => Err(unsupported()),
+        },
+        VariantType::StringArray => match v.to::<PoolArray<GodotString>>() {
+            Some(a) => Ok(Payload::Array(
+                a.to_vec()
+                    .iter()
+                    .map(|x| Payload::String(x.to_string()))
+                    .collect(),
+            )),
+            None => Err(unsupported()),
+        },
+        VariantType::Dictionary => match v.to::<Dictionary>() {
+            Some(d) => d
+                .iter()
+                .map(|(k, x)| variant_to_payload(&x).map(|x| (k.to_string(), x)))
+                .collect::<Result<toml::map::Map<String, Payload>, String>>()
+                .map(Payload::Table),
+            None => Err(unsupported()),
+        },
+        _ => Err(unsupported()),
+    }
+}
+
+/// Converts floats to a `Payload::Array` of `Payload::Float`s.
+fn floats_to_payload(floats: &[f32]) -> Payload {
+    Payload::Array(floats.iter().map(|x| Payload::Float((*x).into())).collect())
+}
+
+/// Converts a `Payload` to a `Variant`. Datetimes are converted to strings.
+fn payload_to_variant(p: &Payload) -> Variant {
+    match p {
+        Payload::String(s) => s.to_variant(),
+        Payload::Integer(i) => i.to_variant(),
+        Payload::Float(f) => f.to_variant(),
+        Payload::Boolean(b) => b.to_variant(),
+        Payload::Datetime(d) => d.to_string().to_variant(),
+        Payload::Array(a) => {
+            let r = VariantArray::new();
+            for x in a.iter() {
+                r.push(payload_to_variant(x));
+            }
+            r.owned_to_variant()
+        }
+        Payload::Table(t) => {
+            let r = Dictionary::new();
+            for (k, x) in t.iter() {
+                r.insert(k, payload_to_variant(x));
+            }
+            r.owned_to_variant()
+        }
+    }
+}
+
 fn init(handle: InitHandle) {
     handle.add_class::<HotkeyListenerNode>();
     handle.add_class::<HotkeyListenerPopup>();
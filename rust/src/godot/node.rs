@@ -1,23 +1,41 @@
+use std::{cell::RefCell, str::FromStr, time::Instant};
+
 use crossbeam_channel::Receiver;
-use gdnative::prelude::*;
+use gdnative::{api::ProjectSettings, prelude::*};
 
-use crate::godot::varray_to_vec;
+use crate::backend::KeyEvent;
+use crate::godot::{payload_to_variant, variant_to_payload, varray_to_steps, varray_to_vec};
 use crate::hotkey_listener::*;
 
 const ACTION_RECEIVED_SIGNAL: &str = "action_pressed";
+const SEQUENCE_ADVANCED_SIGNAL: &str = "sequence_advanced";
+const SEQUENCE_RESET_SIGNAL: &str = "sequence_reset";
+const KEY_RECEIVED_SIGNAL: &str = "key_received";
+const HOTKEY_CAPTURED_SIGNAL: &str = "hotkey_captured";
+const LAYER_ENTERED_SIGNAL: &str = "layer_entered";
+const LAYER_EXITED_SIGNAL: &str = "layer_exited";
 
 /// Godot wrapper for interacting with the base Rust library.
 ///
 /// In the interest of having Godot handle errors, this struct can potentially fail to initialize.
 /// The `is_valid` func should be checked before doing anything with the object. If the object is not valid,
 /// then Godot should clean up the object.
+///
+/// The OS hook only reports key presses, so features that need key releases, i.e. held chord mode, action
+/// releases, auto-repeat and gestures, are not exposed.
 #[derive(NativeClass)]
 #[inherit(Node)]
 #[register_with(Self::register_signals)]
 pub struct HotkeyListenerNode {
     is_valid: bool,
     hotkey_listener: Option<HotkeyListener>,
-    receiver: Receiver<String>,
+    receiver: Receiver<ActionEvent>,
+    /// Only set while raw key events are enabled.
+    key_receiver: Option<Receiver<KeyEvent>>,
+    /// Event timestamps are sent to Godot relative to this.
+    created_at: Instant,
+    /// Code and message of the last error, until it is taken with `take_last_error`.
+    last_error: RefCell<Option<(&'static str, String)>>,
 }
 
 #[methods]
@@ -30,14 +48,20 @@ impl HotkeyListenerNode {
                     is_valid: true,
                     hotkey_listener: Some(hl),
                     receiver: r,
+                    key_receiver: None,
+                    created_at: Instant::now(),
+                    last_error: RefCell::new(None),
                 }
             }
             Err(e) => {
-                godot_error!("An error occurred while setting up HotkeyListener: {:?}", e);
+                godot_error!("An error occurred while setting up HotkeyListener: {}", e);
                 return HotkeyListenerNode {
                     is_valid: false,
                     hotkey_listener: None,
                     receiver: r,
+                    key_receiver: None,
+                    created_at: Instant::now(),
+                    last_error: RefCell::new(Some((e.code(), e.to_string()))),
                 };
             }
         }
@@ -45,6 +69,12 @@ impl HotkeyListenerNode {
 
     fn register_signals(build: &ClassBuilder<Self>) {
         build.signal(ACTION_RECEIVED_SIGNAL).done();
+        build.signal(SEQUENCE_ADVANCED_SIGNAL).done();
+        build.signal(SEQUENCE_RESET_SIGNAL).done();
+        build.signal(KEY_RECEIVED_SIGNAL).done();
+        build.signal(HOTKEY_CAPTURED_SIGNAL).done();
+        build.signal(LAYER_ENTERED_SIGNAL).done();
+        build.signal(LAYER_EXITED_SIGNAL).done();
     }
 
     #[method]
@@ -54,27 +84,153 @@ impl HotkeyListenerNode {
 
     #[method]
     fn _process(&mut self, #[base] owner: &Node, _delta: f32) {
-        let listener = self.hotkey_listener.as_mut().unwrap();
+        // Processing is disabled in `_ready` for invalid nodes, so there is nothing to report
+        let listener = match self.hotkey_listener.as_mut() {
+            Some(l) => l,
+            None => return,
+        };
         listener.poll();
 
-        if self.receiver.is_empty() {
-            return;
+        if let Some(keys) = listener.take_capture() {
+            let r = VariantArray::new();
+            for key in keys.iter() {
+                r.push(key);
+            }
+            owner.emit_signal(HOTKEY_CAPTURED_SIGNAL, &[r.owned_to_variant()]);
         }
-        match self.receiver.recv() {
-            Ok(s) => {
-                owner.emit_signal(
-                    ACTION_RECEIVED_SIGNAL,
-                    &[GodotString::from_str(s).to_variant()],
+
+        if let Some(key_receiver) = self.key_receiver.as_ref() {
+            while let Ok(event) = key_receiver.try_recv() {
+                let name = GodotString::from_str(key_code_name(&event.key)).to_variant();
+
+                let details = Dictionary::new();
+                details.insert("state", event.state.as_str());
+                details.insert(
+                    "timestamp",
+                    event
+                        .time
+                        .saturating_duration_since(self.created_at)
+                        .as_secs_f64(),
                 );
-                return;
+
+                owner.emit_signal(KEY_RECEIVED_SIGNAL, &[name, details.owned_to_variant()]);
+            }
+        }
+
+        while let Ok(event) = self.receiver.try_recv() {
+            let name = GodotString::from_str(&event.action).to_variant();
+            let details = self.event_to_dictionary(&event).owned_to_variant();
+
+            match event.kind {
+                ActionEventKind::Pressed => {
+                    owner.emit_signal(ACTION_RECEIVED_SIGNAL, &[name, details]);
+                }
+                // Only sent when bindings are replaced or a layer is exited, never for an actual key release
+                ActionEventKind::Released => {}
+                // Auto-repeat is refused without key releases
+                ActionEventKind::Repeat => {}
+                ActionEventKind::SequenceAdvanced(step) => {
+                    owner.emit_signal(
+                        SEQUENCE_ADVANCED_SIGNAL,
+                        &[name, step.to_variant(), details],
+                    );
+                }
+                ActionEventKind::SequenceReset => {
+                    owner.emit_signal(SEQUENCE_RESET_SIGNAL, &[name, details]);
+                }
+                ActionEventKind::LayerEntered => {
+                    owner.emit_signal(LAYER_ENTERED_SIGNAL, &[name, details]);
+                }
+                ActionEventKind::LayerExited => {
+                    owner.emit_signal(LAYER_EXITED_SIGNAL, &[name, details]);
+                }
             }
-            Err(e) => godot_error!("{:?}", e),
         }
     }
 
+    /// Converts the parts of an event not already passed as signal arguments into a `Dictionary`.
+    ///
+    /// The timestamp is in seconds since this node was created.
+    fn event_to_dictionary(&self, event: &ActionEvent) -> Dictionary<Unique> {
+        let r = Dictionary::new();
+        r.insert("keys", keys_to_varray(&event.keys));
+        r.insert(
+            "timestamp",
+            event
+                .timestamp
+                .saturating_duration_since(self.created_at)
+                .as_secs_f64(),
+        );
+        r.insert("kind", event.kind.as_str());
+        r.insert("serial", event.serial);
+        r.insert(
+            "payload",
+            match event.payload.as_ref() {
+                Some(p) => payload_to_variant(p),
+                None => Variant::nil(),
+            },
+        );
+
+        r
+    }
+
+    /// Logs an error and keeps it for `take_last_error`.
+    fn report(&self, code: &'static str, message: String) {
+        godot_error!("{}", message);
+        self.last_error.replace(Some((code, message)));
+    }
+
+    /// Logs an error returned by the listener and keeps it for `take_last_error`.
+    fn report_error(&self, e: &Error) {
+        self.report(e.code(), e.to_string());
+    }
+
+    /// Returns the listener, or reports `InvalidNode` if setting it up failed.
+    fn listener(&self) -> Option<&HotkeyListener> {
+        if self.hotkey_listener.is_none() {
+            self.report_invalid();
+        }
+
+        self.hotkey_listener.as_ref()
+    }
+
+    /// Returns the listener, or reports `InvalidNode` if setting it up failed.
+    fn listener_mut(&mut self) -> Option<&mut HotkeyListener> {
+        if self.hotkey_listener.is_none() {
+            self.report_invalid();
+        }
+
+        self.hotkey_listener.as_mut()
+    }
+
+    fn report_invalid(&self) {
+        self.report(
+            "InvalidNode",
+            "HotkeyListenerNode failed to set up, see `is_valid`".to_string(),
+        );
+    }
+
+    /// Returns the last error reported by any method as a `Dictionary` with a `code`, e.g. `ActionDoesNotExist`,
+    /// and a human-readable `message`, then forgets it. Returns an empty `Dictionary` if no error was reported
+    /// since the last call.
+    ///
+    /// Codes are the names of the `Error` variants plus `UnknownResolutionPolicy` and `UnsupportedPayload` for
+    /// invalid arguments, and `InvalidNode` for any call on a node that is not valid.
+    #[method]
+    fn take_last_error(&self) -> Dictionary {
+        let r = Dictionary::new();
+        if let Some((code, message)) = self.last_error.take() {
+            r.insert("code", code);
+            r.insert("message", message);
+        }
+
+        r.into_shared()
+    }
+
     /// Setting up the initial OS hook can fail. If initial setup fails, then this class is no longer valid.
     ///
-    /// This **MUST** be checked first since all other functions assume the setup succeeded.
+    /// This **MUST** be checked first. On an invalid node, every other method does nothing, reports
+    /// `InvalidNode` and returns `false` or an empty value.
     #[method]
     fn is_valid(&self) -> bool {
         self.is_valid
@@ -83,12 +239,15 @@ impl HotkeyListenerNode {
     /// Godot -> Rust wrapper
     #[method]
     fn register_action(&mut self, name: GodotString, keys: VariantArray) -> bool {
-        let listener = self.hotkey_listener.as_mut().unwrap();
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
 
         match listener.register_action(&name.to_string(), varray_to_vec(&keys).as_slice()) {
             Ok(_) => true,
             Err(e) => {
-                godot_error!("{:?}", e);
+                self.report_error(&e);
                 false
             }
         }
@@ -97,47 +256,275 @@ impl HotkeyListenerNode {
     /// Godot -> Rust wrapper
     #[method]
     fn unregister_action(&mut self, name: GodotString, keys: VariantArray) -> bool {
-        let listener = self.hotkey_listener.as_mut().unwrap();
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
 
         match listener.unregister_action(&name.to_string(), varray_to_vec(&keys).as_slice()) {
             Ok(_) => true,
             Err(e) => {
-                godot_error!("{:?}", e);
+                self.report_error(&e);
                 false
             }
         }
     }
 
     /// Godot -> Rust wrapper
+    ///
+    /// `steps` is an `Array` of `Array`s of key names, e.g. `[["ControlLeft", "K"], ["ControlLeft", "S"]]`.
     #[method]
-    fn get_min_elapsed_time(&self) -> f32 {
-        self.hotkey_listener
-            .as_ref()
-            .unwrap()
-            .get_min_elapsed_time()
+    fn register_sequence(&mut self, name: GodotString, steps: VariantArray) -> bool {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener.register_sequence(&name.to_string(), varray_to_steps(&steps).as_slice()) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
     }
 
     /// Godot -> Rust wrapper
     #[method]
-    fn set_min_elapsed_time(&mut self, min_elapsed_time: f32) {
-        self.hotkey_listener
-            .as_mut()
-            .unwrap()
-            .set_min_elapsed_time(min_elapsed_time);
+    fn unregister_sequence(&mut self, name: GodotString, steps: VariantArray) -> bool {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener.unregister_sequence(&name.to_string(), varray_to_steps(&steps).as_slice()) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
     }
 
     /// Godot -> Rust wrapper
+    ///
+    /// Accepts hotkey strings like `Ctrl+Shift+A` or `Ctrl+K, Ctrl+S`.
     #[method]
-    fn get_action_names(&self) -> VariantArray {
+    fn register_hotkey(&mut self, name: GodotString, hotkey: GodotString) -> bool {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener.register_hotkey(&name.to_string(), &hotkey.to_string()) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn unregister_hotkey(&mut self, name: GodotString, hotkey: GodotString) -> bool {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener.unregister_hotkey(&name.to_string(), &hotkey.to_string()) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// Returns an `Array` of steps, each an `Array` of key names. Returns an empty `Array` if the hotkey
+    /// string is invalid.
+    #[method]
+    fn parse_hotkey(&self, hotkey: GodotString) -> VariantArray {
         let r = VariantArray::new();
 
-        for n in self
-            .hotkey_listener
-            .as_ref()
-            .unwrap()
-            .get_action_names()
-            .iter()
-        {
+        match parse_hotkey(&hotkey.to_string()) {
+            Ok(steps) => {
+                for step in steps.iter() {
+                    let keys = VariantArray::new();
+                    for key in step.iter() {
+                        keys.push(key);
+                    }
+                    r.push(keys.into_shared());
+                }
+            }
+            Err(e) => self.report_error(&e),
+        }
+
+        r.into_shared()
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// Accepts an `Array` of steps, each an `Array` of key names. Returns an empty string if a key name is
+    /// invalid.
+    #[method]
+    fn format_hotkey(&self, steps: VariantArray) -> GodotString {
+        match format_hotkey(varray_to_steps(&steps).as_slice()) {
+            Ok(s) => GodotString::from_str(s),
+            Err(e) => {
+                self.report_error(&e);
+                GodotString::new()
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// An empty `leader` means the layer can only be entered with `push_layer`. A `timeout` of 0 means the
+    /// layer stays active until it is exited with Escape or `pop_layer`.
+    #[method]
+    fn register_layer(&mut self, name: GodotString, leader: VariantArray, timeout: f32) -> bool {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+        let timeout = if timeout > 0.0 { Some(timeout) } else { None };
+
+        match listener.register_layer(
+            &name.to_string(),
+            varray_to_vec(&leader).as_slice(),
+            timeout,
+        ) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn unregister_layer(&mut self, name: GodotString) -> bool {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener.unregister_layer(&name.to_string()) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn register_layer_action(
+        &mut self,
+        layer: GodotString,
+        name: GodotString,
+        keys: VariantArray,
+    ) -> bool {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener.register_layer_action(
+            &layer.to_string(),
+            &name.to_string(),
+            varray_to_vec(&keys).as_slice(),
+        ) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn unregister_layer_action(
+        &mut self,
+        layer: GodotString,
+        name: GodotString,
+        keys: VariantArray,
+    ) -> bool {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener.unregister_layer_action(
+            &layer.to_string(),
+            &name.to_string(),
+            varray_to_vec(&keys).as_slice(),
+        ) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn push_layer(&mut self, name: GodotString) -> bool {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener.push_layer(&name.to_string()) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// Returns the name of the exited layer, or an empty string if no layer was active.
+    #[method]
+    fn pop_layer(&mut self) -> GodotString {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return GodotString::new(),
+        };
+
+        match listener.pop_layer() {
+            Ok(Some(name)) => GodotString::from_str(name),
+            Ok(None) => GodotString::new(),
+            Err(e) => {
+                self.report_error(&e);
+                GodotString::new()
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// The top-most layer is last.
+    #[method]
+    fn get_layer_stack(&self) -> VariantArray {
+        let listener = match self.listener() {
+            Some(l) => l,
+            None => return VariantArray::new_shared(),
+        };
+
+        let r = VariantArray::new();
+
+        for n in listener.get_layer_stack().iter() {
             r.push(n);
         }
 
@@ -146,19 +533,851 @@ impl HotkeyListenerNode {
 
     /// Godot -> Rust wrapper
     #[method]
-    fn get_key_names(&self) -> VariantArray {
+    fn get_layer_names(&self) -> VariantArray {
+        let listener = match self.listener() {
+            Some(l) => l,
+            None => return VariantArray::new_shared(),
+        };
+
         let r = VariantArray::new();
 
-        for n in self
-            .hotkey_listener
-            .as_ref()
-            .unwrap()
-            .get_key_names()
-            .iter()
-        {
+        for n in listener.get_layer_names().iter() {
             r.push(n);
         }
 
         r.into_shared()
     }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn get_min_elapsed_time(&self) -> f32 {
+        let listener = match self.listener() {
+            Some(l) => l,
+            None => return 0.0,
+        };
+
+        listener.get_min_elapsed_time()
+    }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn set_min_elapsed_time(&mut self, min_elapsed_time: f32) -> bool {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener.set_min_elapsed_time(min_elapsed_time) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// Returns the listener's minimum elapsed time if the key sequence has no override.
+    #[method]
+    fn get_binding_min_elapsed_time(&self, keys: VariantArray) -> f32 {
+        let listener = match self.listener() {
+            Some(l) => l,
+            None => return 0.0,
+        };
+
+        match listener.get_binding_min_elapsed_time(varray_to_vec(&keys).as_slice()) {
+            Ok(min_elapsed_time) => {
+                min_elapsed_time.unwrap_or_else(|| listener.get_min_elapsed_time())
+            }
+            Err(e) => {
+                self.report_error(&e);
+                listener.get_min_elapsed_time()
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn set_binding_min_elapsed_time(&mut self, keys: VariantArray, min_elapsed_time: f32) -> bool {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener
+            .set_binding_min_elapsed_time(varray_to_vec(&keys).as_slice(), Some(min_elapsed_time))
+        {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// Makes the key sequence use the listener's minimum elapsed time again.
+    #[method]
+    fn clear_binding_min_elapsed_time(&mut self, keys: VariantArray) -> bool {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener.set_binding_min_elapsed_time(varray_to_vec(&keys).as_slice(), None) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// Returns `null` if the action has no payload for the key sequence.
+    #[method]
+    fn get_binding_payload(&self, name: GodotString, keys: VariantArray) -> Variant {
+        let listener = match self.listener() {
+            Some(l) => l,
+            None => return Variant::nil(),
+        };
+
+        match listener.get_binding_payload(&name.to_string(), varray_to_vec(&keys).as_slice()) {
+            Ok(Some(p)) => payload_to_variant(&p),
+            Ok(None) => Variant::nil(),
+            Err(e) => {
+                self.report_error(&e);
+                Variant::nil()
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// Accepts booleans, numbers, strings, node paths, vectors, colors, arrays, pool arrays and dictionaries.
+    /// Vectors and colors are stored as arrays of floats. `null` removes the payload.
+    #[method]
+    fn set_binding_payload(
+        &mut self,
+        name: GodotString,
+        keys: VariantArray,
+        payload: Variant,
+    ) -> bool {
+        let payload = match payload_from_variant(&payload) {
+            Ok(p) => p,
+            Err(message) => {
+                self.report("UnsupportedPayload", message);
+                return false;
+            }
+        };
+
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener.set_binding_payload(
+            &name.to_string(),
+            varray_to_vec(&keys).as_slice(),
+            payload,
+        ) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// Returns `null` if the action has no payload for the ordered sequence.
+    #[method]
+    fn get_sequence_payload(&self, name: GodotString, steps: VariantArray) -> Variant {
+        let listener = match self.listener() {
+            Some(l) => l,
+            None => return Variant::nil(),
+        };
+
+        match listener.get_sequence_payload(&name.to_string(), varray_to_steps(&steps).as_slice()) {
+            Ok(Some(p)) => payload_to_variant(&p),
+            Ok(None) => Variant::nil(),
+            Err(e) => {
+                self.report_error(&e);
+                Variant::nil()
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// Accepts the same payloads as `set_binding_payload`.
+    #[method]
+    fn set_sequence_payload(
+        &mut self,
+        name: GodotString,
+        steps: VariantArray,
+        payload: Variant,
+    ) -> bool {
+        let payload = match payload_from_variant(&payload) {
+            Ok(p) => p,
+            Err(message) => {
+                self.report("UnsupportedPayload", message);
+                return false;
+            }
+        };
+
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener.set_sequence_payload(
+            &name.to_string(),
+            varray_to_steps(&steps).as_slice(),
+            payload,
+        ) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// Returns `null` if the action has no payload for the key sequence in the layer.
+    #[method]
+    fn get_layer_action_payload(
+        &self,
+        layer: GodotString,
+        name: GodotString,
+        keys: VariantArray,
+    ) -> Variant {
+        let listener = match self.listener() {
+            Some(l) => l,
+            None => return Variant::nil(),
+        };
+
+        match listener.get_layer_action_payload(
+            &layer.to_string(),
+            &name.to_string(),
+            varray_to_vec(&keys).as_slice(),
+        ) {
+            Ok(Some(p)) => payload_to_variant(&p),
+            Ok(None) => Variant::nil(),
+            Err(e) => {
+                self.report_error(&e);
+                Variant::nil()
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// Accepts the same payloads as `set_binding_payload`.
+    #[method]
+    fn set_layer_action_payload(
+        &mut self,
+        layer: GodotString,
+        name: GodotString,
+        keys: VariantArray,
+        payload: Variant,
+    ) -> bool {
+        let payload = match payload_from_variant(&payload) {
+            Ok(p) => p,
+            Err(message) => {
+                self.report("UnsupportedPayload", message);
+                return false;
+            }
+        };
+
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener.set_layer_action_payload(
+            &layer.to_string(),
+            &name.to_string(),
+            varray_to_vec(&keys).as_slice(),
+            payload,
+        ) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// Accepts Godot paths like `user://hotkeys.toml`.
+    #[method]
+    fn save_bindings(&self, path: GodotString) -> bool {
+        let listener = match self.listener() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        let path = ProjectSettings::godot_singleton().globalize_path(path);
+
+        match listener.save_bindings(path.to_string()) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// Accepts Godot paths like `user://hotkeys.toml`. Either every binding in the file is loaded or
+    /// none are.
+    #[method]
+    fn load_bindings(&mut self, path: GodotString) -> bool {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        let path = ProjectSettings::godot_singleton().globalize_path(path);
+
+        match listener.load_bindings(path.to_string()) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// Returns a `Dictionary` with the keys `duplicates`, `overlaps` and `shared_keys`. Each is an `Array` of
+    /// `Dictionary`s:
+    /// - `duplicates`: `{ keys, actions }`
+    /// - `overlaps`: `{ subset: { keys, actions }, superset: { keys, actions } }`
+    /// - `shared_keys`: `{ first: { keys, actions }, second: { keys, actions }, shared }`
+    #[method]
+    fn find_conflicts(&self) -> Dictionary {
+        let listener = match self.listener() {
+            Some(l) => l,
+            None => return Dictionary::new_shared(),
+        };
+
+        let duplicates = VariantArray::new();
+        let overlaps = VariantArray::new();
+        let shared_keys = VariantArray::new();
+
+        for conflict in listener.find_conflicts() {
+            match conflict {
+                Conflict::Duplicate(binding) => {
+                    duplicates.push(conflicting_binding_to_dictionary(&binding));
+                }
+                Conflict::Overlap { subset, superset } => {
+                    let d = Dictionary::new();
+                    d.insert("subset", conflicting_binding_to_dictionary(&subset));
+                    d.insert("superset", conflicting_binding_to_dictionary(&superset));
+                    overlaps.push(d);
+                }
+                Conflict::SharedKeys {
+                    first,
+                    second,
+                    shared,
+                } => {
+                    let d = Dictionary::new();
+                    d.insert("first", conflicting_binding_to_dictionary(&first));
+                    d.insert("second", conflicting_binding_to_dictionary(&second));
+                    d.insert("shared", keys_to_varray(&shared));
+                    shared_keys.push(d);
+                }
+            }
+        }
+
+        let r = Dictionary::new();
+        r.insert("duplicates", duplicates);
+        r.insert("overlaps", overlaps);
+        r.insert("shared_keys", shared_keys);
+
+        r.into_shared()
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// Returns an `Array` of `Dictionary`s with a `code` and a `message`, one for each internal inconsistency.
+    /// Returns an empty `Array` if the listener is consistent.
+    #[method]
+    fn validate(&self) -> VariantArray {
+        let listener = match self.listener() {
+            Some(l) => l,
+            None => return VariantArray::new_shared(),
+        };
+
+        let r = VariantArray::new();
+        if let Err(errors) = listener.validate() {
+            for e in errors.iter() {
+                let d = Dictionary::new();
+                d.insert("code", e.code());
+                d.insert("message", e.to_string());
+                r.push(d);
+            }
+        }
+
+        r.into_shared()
+    }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn get_profiles(&self) -> VariantArray {
+        let listener = match self.listener() {
+            Some(l) => l,
+            None => return VariantArray::new_shared(),
+        };
+
+        let r = VariantArray::new();
+
+        for n in listener.get_profiles().iter() {
+            r.push(n);
+        }
+
+        r.into_shared()
+    }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn get_active_profile(&self) -> GodotString {
+        let listener = match self.listener() {
+            Some(l) => l,
+            None => return GodotString::new(),
+        };
+
+        GodotString::from_str(listener.get_active_profile())
+    }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn set_active_profile(&mut self, name: GodotString) -> bool {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener.set_active_profile(&name.to_string()) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn create_profile(&mut self, name: GodotString) -> bool {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener.create_profile(&name.to_string()) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn remove_profile(&mut self, name: GodotString) -> bool {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener.remove_profile(&name.to_string()) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// Returns 0 if there is no limit.
+    #[method]
+    fn get_max_events_per_poll(&self) -> i64 {
+        let listener = match self.listener() {
+            Some(l) => l,
+            None => return 0,
+        };
+
+        match listener.get_max_events_per_poll() {
+            Some(max) => max as i64,
+            None => 0,
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// Values less than 1 remove the limit.
+    #[method]
+    fn set_max_events_per_poll(&mut self, max_events_per_poll: i64) {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return,
+        };
+
+        listener.set_max_events_per_poll(if max_events_per_poll > 0 {
+            Some(max_events_per_poll as usize)
+        } else {
+            None
+        });
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// The recorded keys are emitted through `hotkey_captured` once the session finishes.
+    #[method]
+    fn start_capture(&mut self) -> bool {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener.start_capture() {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn cancel_capture(&mut self) -> bool {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener.cancel_capture() {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn is_capturing(&self) -> bool {
+        let listener = match self.listener() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        listener.is_capturing()
+    }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn get_capture_all(&self) -> bool {
+        let listener = match self.listener() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        listener.get_capture_all()
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// Combine with `set_key_events_enabled` to receive `key_received` for every key.
+    #[method]
+    fn set_capture_all(&mut self, capture_all: bool) -> bool {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener.set_capture_all(capture_all) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn get_key_events_enabled(&self) -> bool {
+        self.key_receiver.is_some()
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// While enabled, every press and release of a hooked key is emitted through `key_received`.
+    #[method]
+    fn set_key_events_enabled(&mut self, enabled: bool) {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return,
+        };
+
+        if enabled {
+            let (s, r) = crossbeam_channel::unbounded();
+            listener.set_key_sender(Some(s));
+            self.key_receiver = Some(r);
+        } else {
+            listener.set_key_sender(None);
+            self.key_receiver = None;
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn get_edge_triggered(&self) -> bool {
+        let listener = match self.listener() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        listener.get_edge_triggered()
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// Enabling it is refused with `ReleasesNotReported`, since the OS hook only reports key presses.
+    #[method]
+    fn set_edge_triggered(&mut self, edge_triggered: bool) -> bool {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener.set_edge_triggered(edge_triggered) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// Returns 0 if the action has no cooldown.
+    #[method]
+    fn get_action_cooldown(&self, name: GodotString) -> f32 {
+        let listener = match self.listener() {
+            Some(l) => l,
+            None => return 0.0,
+        };
+
+        listener
+            .get_action_cooldown(&name.to_string())
+            .unwrap_or(0.0)
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// Values less than or equal to 0 remove the cooldown.
+    #[method]
+    fn set_action_cooldown(&mut self, name: GodotString, cooldown: f32) -> bool {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener.set_action_cooldown(
+            &name.to_string(),
+            if cooldown > 0.0 { Some(cooldown) } else { None },
+        ) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn get_sequence_timeout(&self) -> f32 {
+        let listener = match self.listener() {
+            Some(l) => l,
+            None => return 0.0,
+        };
+
+        listener.get_sequence_timeout()
+    }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn set_sequence_timeout(&mut self, sequence_timeout: f32) -> bool {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener.set_sequence_timeout(sequence_timeout) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn get_resolution_policy(&self) -> GodotString {
+        let listener = match self.listener() {
+            Some(l) => l,
+            None => return GodotString::new(),
+        };
+
+        GodotString::from_str(listener.get_resolution_policy().as_str())
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// Accepts `FireAll`, `LongestMatch`, `FirstRegistered` or `Priority`.
+    #[method]
+    fn set_resolution_policy(&mut self, resolution_policy: GodotString) -> bool {
+        let rp = match ResolutionPolicy::from_str(&resolution_policy.to_string()) {
+            Ok(rp) => rp,
+            Err(_) => {
+                self.report(
+                    "UnknownResolutionPolicy",
+                    format!("unknown resolution policy `{resolution_policy}`"),
+                );
+                return false;
+            }
+        };
+
+        match self.listener_mut() {
+            Some(listener) => {
+                listener.set_resolution_policy(rp);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    ///
+    /// Returns 0 if the key sequence is not registered.
+    #[method]
+    fn get_binding_priority(&self, keys: VariantArray) -> i32 {
+        let listener = match self.listener() {
+            Some(l) => l,
+            None => return 0,
+        };
+
+        match listener.get_binding_priority(varray_to_vec(&keys).as_slice()) {
+            Ok(priority) => priority,
+            Err(e) => {
+                self.report_error(&e);
+                0
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn set_binding_priority(&mut self, keys: VariantArray, priority: i32) -> bool {
+        let listener = match self.listener_mut() {
+            Some(l) => l,
+            None => return false,
+        };
+
+        match listener.set_binding_priority(varray_to_vec(&keys).as_slice(), priority) {
+            Ok(_) => true,
+            Err(e) => {
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn get_action_names(&self) -> VariantArray {
+        let listener = match self.listener() {
+            Some(l) => l,
+            None => return VariantArray::new_shared(),
+        };
+
+        let r = VariantArray::new();
+
+        for n in listener.get_action_names().iter() {
+            r.push(n);
+        }
+
+        r.into_shared()
+    }
+
+    /// Godot -> Rust wrapper
+    #[method]
+    fn get_key_names(&self) -> VariantArray {
+        let listener = match self.listener() {
+            Some(l) => l,
+            None => return VariantArray::new_shared(),
+        };
+
+        let r = VariantArray::new();
+
+        for n in listener.get_key_names().iter() {
+            r.push(n);
+        }
+
+        r.into_shared()
+    }
+}
+
+fn conflicting_binding_to_dictionary(binding: &ConflictingBinding) -> Dictionary<Unique> {
+    let actions = VariantArray::new();
+    for action in binding.actions.iter() {
+        actions.push(action);
+    }
+
+    let r = Dictionary::new();
+    r.insert("keys", keys_to_varray(&binding.keys));
+    r.insert("actions", actions);
+
+    r
+}
+
+fn keys_to_varray(keys: &[Key]) -> VariantArray<Unique> {
+    let r = VariantArray::new();
+    for key in keys.iter() {
+        r.push(key.name());
+    }
+
+    r
+}
+
+/// Converts a `Variant` passed to a payload setter. `null` becomes `None`. Returns an error message for types
+/// that cannot be converted.
+fn payload_from_variant(v: &Variant) -> std::result::Result<Option<Payload>, String> {
+    if v.is_nil() {
+        return Ok(None);
+    }
+
+    variant_to_payload(v).map(Some)
 }
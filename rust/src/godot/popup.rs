@@ -19,6 +19,8 @@ pub struct HotkeyListenerPopup {
     key_names: Vec<GodotString>,
     modifier_names: Vec<GodotString>,
     values_label: Ref<Label, Shared>,
+    /// The `HotkeyListenerNode` used for capturing keys, if any. See `capture_with`.
+    listener: Option<Ref<Node>>,
 }
 
 #[methods]
@@ -29,6 +31,7 @@ impl HotkeyListenerPopup {
             key_names: vec![],
             modifier_names: vec![],
             values_label: Label::new().into_shared(),
+            listener: None,
         }
     }
 
@@ -92,22 +95,24 @@ impl HotkeyListenerPopup {
         let confirm = Button::new();
         confirm.set_h_size_flags(SizeFlags::EXPAND.0 + SizeFlags::SHRINK_CENTER.0);
         confirm.set_text("HOTKEY_LISTENER_POPUP_WINDOW_CONFIRM");
-        confirm
-            .connect(
-                "pressed",
-                owner,
-                "_on_confirm",
-                VariantArray::new_shared(),
-                0,
-            )
-            .unwrap();
+        match confirm.connect(
+            "pressed",
+            owner,
+            "_on_confirm",
+            VariantArray::new_shared(),
+            0,
+        ) {
+            Ok(_) => {}
+            Err(e) => godot_error!("{:?}", e),
+        }
 
         let cancel = Button::new();
         cancel.set_h_size_flags(SizeFlags::EXPAND.0 + SizeFlags::SHRINK_CENTER.0);
         cancel.set_text("HOTKEY_LISTENER_POPUP_WINDOW_CANCEL");
-        cancel
-            .connect("pressed", owner, "_on_hide", VariantArray::new_shared(), 0)
-            .unwrap();
+        match cancel.connect("pressed", owner, "_on_hide", VariantArray::new_shared(), 0) {
+            Ok(_) => {}
+            Err(e) => godot_error!("{:?}", e),
+        }
 
         confirm_cancel_box.add_child(confirm, false);
         confirm_cancel_box.add_child(cancel, false);
@@ -118,19 +123,75 @@ impl HotkeyListenerPopup {
 
         owner.add_child(vbox, false);
 
-        owner
-            .connect(
-                "popup_hide",
-                owner,
-                "_on_hide",
-                VariantArray::new_shared(),
-                0,
-            )
-            .unwrap();
+        match owner.connect(
+            "popup_hide",
+            owner,
+            "_on_hide",
+            VariantArray::new_shared(),
+            0,
+        ) {
+            Ok(_) => {}
+            Err(e) => godot_error!("{:?}", e),
+        }
+    }
+
+    /// Records keys with a global capture session on `listener` instead of `_input`, so keys are recorded
+    /// even while another application is focused. `listener` must be a `HotkeyListenerNode`.
+    ///
+    /// Can only be called once per popup. Further calls are reported as errors and change nothing.
+    #[method]
+    fn capture_with(&mut self, #[base] owner: TRef<WindowDialog>, listener: Ref<Node>) {
+        if self.listener.is_some() {
+            godot_error!("capture_with was already called on this popup");
+            return;
+        }
+
+        let node = unsafe { listener.assume_safe() };
+        if !node.has_method("start_capture") {
+            godot_error!("capture_with expects a HotkeyListenerNode");
+            return;
+        }
+
+        if let Err(e) = node.connect(
+            "hotkey_captured",
+            owner,
+            "_on_hotkey_captured",
+            VariantArray::new_shared(),
+            0,
+        ) {
+            godot_error!("{:?}", e);
+            return;
+        }
+        unsafe { node.call("start_capture", &[]) };
+
+        self.listener = Some(listener);
+    }
+
+    #[method]
+    fn _on_hotkey_captured(&mut self, keys: VariantArray) {
+        let label = unsafe { self.values_label.assume_unique() };
+
+        self.key_names = keys
+            .iter()
+            .map(|k| GodotString::from_str(k.to_string()))
+            .collect();
+        label.set_text(GodotString::from_str(
+            keys.owned_to_variant().to_string().as_str(),
+        ));
+
+        // Keep capturing so that the last chord pressed before confirming wins. Deferred since the
+        // listener is still emitting the signal.
+        if let Some(listener) = self.listener.as_ref() {
+            unsafe { listener.assume_safe().call_deferred("start_capture", &[]) };
+        }
     }
 
     #[method]
     fn _input(&mut self, event: Ref<InputEvent>) {
+        if self.listener.is_some() {
+            return;
+        }
+
         let event = unsafe { event.assume_safe() };
 
         let event = match event.cast::<InputEventKey>() {
@@ -189,6 +250,10 @@ impl HotkeyListenerPopup {
 
     #[method]
     fn _on_hide(&self, #[base] owner: TRef<WindowDialog>) {
+        if let Some(listener) = self.listener.as_ref() {
+            unsafe { listener.assume_safe().call_deferred("cancel_capture", &[]) };
+        }
+
         owner.queue_free();
     }
 
@@ -222,7 +287,7 @@ fn setup_modifier(
         KeyCode::MetaRight => "Right super",
         KeyCode::ShiftLeft => "Left shift",
         KeyCode::ShiftRight => "Right shift",
-        _ => unreachable!(),
+        _ => key_code.as_str(),
     });
 
     let args = VariantArray::new();
@@ -233,15 +298,16 @@ fn setup_modifier(
             GodotString::from_str("Unknown key")
         }
     });
-    check_box
-        .connect(
-            "toggled",
-            owner,
-            "_on_modifier_toggled",
-            args.into_shared(),
-            0,
-        )
-        .unwrap();
+    match check_box.connect(
+        "toggled",
+        owner,
+        "_on_modifier_toggled",
+        args.into_shared(),
+        0,
+    ) {
+        Ok(_) => {}
+        Err(e) => godot_error!("{:?}", e),
+    }
 }
 
 macro_rules! generate_kc_sc_mapping {
@@ -1,84 +1,287 @@
 use std::{
-    collections::{hash_map::DefaultHasher, HashMap},
-    hash::{Hash, Hasher},
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    hash::Hash,
+    mem,
+    path::Path,
     str::FromStr,
     time::{Duration, Instant},
 };
 
 use crossbeam_channel::{unbounded, Receiver, Sender};
-use livesplit_hotkey::{Hook, KeyCode};
+use livesplit_hotkey::KeyCode;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug)]
-pub enum Error {
-    HookCreate,
+use crate::backend::{Backend, KeyEvent, KeyState, LivesplitBackend};
+
+mod bindings;
+pub use bindings::{Binding, BindingError, Bindings};
+
+mod capture;
+use capture::CaptureSession;
+
+mod conflicts;
+pub use conflicts::{Conflict, ConflictingBinding};
+
+mod error;
+use error::{keys_to_string, steps_to_string};
+pub use error::{Error, MapType, Result};
+
+mod event;
+use event::EventEmitter;
+pub use event::{ActionEvent, ActionEventKind};
+
+mod gesture;
+pub use gesture::Gesture;
+use gesture::GestureMapping;
+
+mod hotkey_string;
+pub use hotkey_string::{format_hotkey, parse_hotkey};
 
-    ActionAlreadyExists,
-    ActionDoesNotExist(MapType),
-    KeyNotMapped,
+mod key;
+use key::keys_imply;
+pub use key::Key;
 
-    MappedKeyMissingInReverseLookup,
+mod key_set;
+pub use key_set::KeySet;
 
-    BadKeyCodeName,
-    CannotRegisterHotkey(livesplit_hotkey::Error),
-    CannotUnregisterHotkey(livesplit_hotkey::Error),
+mod key_codes;
+pub use key_codes::ALL_KEY_CODES;
+
+mod layer;
+use layer::{ActiveLayer, Layer};
+
+mod sequence;
+use sequence::{SequenceMapping, SequenceProgress};
+
+mod worker;
+pub use worker::{ListenerGuard, ListenerThread};
+
+/// Arbitrary data delivered with every event of an action, see `HotkeyListener::set_binding_payload`.
+///
+/// Any `Serialize` type can be converted with `Payload::try_from`.
+pub type Payload = toml::Value;
+
+/// Decides which key sequences fire when a single key press completes several of them at once,
+/// e.g. pressing `A` while holding Control and Shift with both `Ctrl+A` and `Ctrl+Shift+A` registered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolutionPolicy {
+    /// Every completed key sequence fires.
+    FireAll,
+    /// Key sequences that are contained in another completed key sequence are suppressed.
+    LongestMatch,
+    /// Only the key sequence that was registered first fires.
+    FirstRegistered,
+    /// Only the key sequences with the highest priority fire. See `HotkeyListener::set_binding_priority`.
+    Priority,
 }
 
-#[derive(Debug)]
-pub enum MapType {
-    Actions,
-    ActionMapping,
-    ReverseLookup,
+impl ResolutionPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResolutionPolicy::FireAll => "FireAll",
+            ResolutionPolicy::LongestMatch => "LongestMatch",
+            ResolutionPolicy::FirstRegistered => "FirstRegistered",
+            ResolutionPolicy::Priority => "Priority",
+        }
+    }
+}
+
+impl FromStr for ResolutionPolicy {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "FireAll" => Ok(ResolutionPolicy::FireAll),
+            "LongestMatch" => Ok(ResolutionPolicy::LongestMatch),
+            "FirstRegistered" => Ok(ResolutionPolicy::FirstRegistered),
+            "Priority" => Ok(ResolutionPolicy::Priority),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Emits `ActionEventKind::Repeat` while a key sequence is held, see `HotkeyListener::set_binding_repeat`.
+/// Times are in seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AutoRepeat {
+    /// Time between the key sequence being pressed and the first repeat.
+    pub delay: f32,
+    /// Time between repeats.
+    pub interval: f32,
+}
+
+impl AutoRepeat {
+    /// Converts the delay and interval to `Duration`s. The interval must be greater than 0.
+    pub(crate) fn to_durations(self) -> Result<(Duration, Duration)> {
+        let delay = secs_to_duration(self.delay, "repeat delay")?;
+        let interval = secs_to_duration(self.interval, "repeat interval")?;
+        if interval.is_zero() {
+            return Err(Error::InvalidDuration {
+                what: "repeat interval",
+                secs: self.interval,
+            });
+        }
+
+        Ok((delay, interval))
+    }
+}
+
+/// Name of the profile that is active when a `HotkeyListener` is created.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// How a `HotkeyListener` decides that a key sequence has been pressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChordMode {
+    /// Every key was pressed within the minimum elapsed time, regardless of whether it was released.
+    Timed,
+    /// Every key is physically held down at the same time. Requires a `Backend` that reports releases.
+    Held,
+}
+
+impl ChordMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChordMode::Timed => "Timed",
+            ChordMode::Held => "Held",
+        }
+    }
 }
 
-type Result<T> = std::result::Result<T, Error>;
+impl FromStr for ChordMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "Timed" => Ok(ChordMode::Timed),
+            "Held" => Ok(ChordMode::Held),
+            _ => Err(()),
+        }
+    }
+}
 
 /// Stores all actions associated with a key sequence along with the last-pressed time for each key.
 #[derive(Debug)]
 struct ActionMapping {
     actions: Vec<String>,
-    keys: HashMap<KeyCode, Instant>,
+    /// When each key was last pressed. `None` until the key is pressed for the first time.
+    keys: HashMap<Key, Option<Instant>>,
+    /// Whether the key sequence has been pressed and none of its keys have been released since.
+    is_active: bool,
+    /// Used by `ResolutionPolicy::Priority`. Higher wins.
+    priority: i32,
+    /// Used by `ResolutionPolicy::FirstRegistered`. Lower wins.
+    registration: u64,
+    /// Overrides the listener's `min_elapsed_time` for this key sequence when set.
+    min_elapsed_time: Option<Duration>,
+    /// Initial delay and interval for repeats.
+    repeat: Option<(Duration, Duration)>,
+    /// When the next repeat is due. Only set while the key sequence is active.
+    next_repeat: Option<Instant>,
+    /// Data delivered with the events of each action.
+    payloads: HashMap<String, Payload>,
 }
 
 impl ActionMapping {
-    fn new(keys: &[KeyCode]) -> Self {
+    fn new(keys: &[Key], registration: u64) -> Self {
         let mut hm = HashMap::new();
-        let offset = Duration::from_secs(60);
         for key in keys.iter() {
-            hm.insert(key.clone(), Instant::now() - offset);
+            hm.insert(*key, None);
         }
 
         ActionMapping {
             actions: vec![],
             keys: hm,
+            is_active: false,
+            priority: 0,
+            registration,
+            min_elapsed_time: None,
+            repeat: None,
+            next_repeat: None,
+            payloads: HashMap::new(),
         }
     }
 
-    /// Update the last pressed time for a given keycode.
-    /// Panics if the key does not exist as this should not be possible.
-    fn press_key(&mut self, key: &KeyCode) {
-        match self.keys.get_mut(key) {
-            Some(time) => *time = Instant::now(),
-            None => unreachable!(),
+    /// Checks if pressing `other` always presses this key sequence, but not the other way around. Keys are
+    /// compared by the physical keys matching them, so `Control+KeyA` is a subset of `ControlLeft+KeyA`.
+    fn is_strict_subset_of(&self, other: &ActionMapping) -> bool {
+        let keys = self.keys.keys().copied().collect::<Vec<Key>>();
+        let other_keys = other.keys.keys().copied().collect::<Vec<Key>>();
+
+        keys_imply(&other_keys, &keys) && !keys_imply(&keys, &other_keys)
+    }
+
+    /// Update the last pressed time for every key matched by the given keycode to `time`.
+    ///
+    /// Returns `false` if no key matches, which means the lookup that led here is out of date.
+    fn press_key(&mut self, key: &KeyCode, time: Instant) -> bool {
+        let mut is_matched = false;
+        for (k, pressed_at) in self.keys.iter_mut() {
+            if k.matches(key) {
+                *pressed_at = Some(time);
+                is_matched = true;
+            }
         }
+
+        is_matched
     }
 
     /// Iterates through every single key's timestamp and compares it to the passed
-    /// `min_elapsed_time`. If all timestamps are less tan the `min_elapsed_time`,
+    /// `min_elapsed_time`. If no more than `min_elapsed_time` has elapsed between any key press and `now`,
     /// then the Action is considered to be pressed.
-    fn is_pressed(&self, min_elapsed_time: &Duration) -> bool {
-        for time in self.keys.values() {
-            if time.elapsed() > *min_elapsed_time {
-                return false;
+    fn is_pressed(&self, min_elapsed_time: &Duration, now: Instant) -> bool {
+        for pressed_at in self.keys.values() {
+            match pressed_at {
+                Some(time) if now.saturating_duration_since(*time) <= *min_elapsed_time => {}
+                _ => return false,
             }
         }
 
         true
     }
 
+    /// Returns every key in the sequence, sorted by name.
+    fn sorted_keys(&self) -> Vec<Key> {
+        let mut r = self.keys.keys().copied().collect::<Vec<Key>>();
+        r.sort_by_cached_key(Key::name);
+
+        r
+    }
+
+    /// Updates the last pressed time for the key to `time`, when the backend observed the press, and checks if
+    /// the key sequence is now considered pressed.
+    fn press(
+        &mut self,
+        key: &KeyCode,
+        time: Instant,
+        chord_mode: ChordMode,
+        min_elapsed_time: &Duration,
+        held: &HashSet<KeyCode>,
+    ) -> bool {
+        if !self.press_key(key, time) {
+            return false;
+        }
+
+        match chord_mode {
+            ChordMode::Timed => {
+                self.is_pressed(&self.min_elapsed_time.unwrap_or(*min_elapsed_time), time)
+            }
+            ChordMode::Held => self.is_held(held),
+        }
+    }
+
+    /// Checks if every key in the sequence is matched by the set of currently `held` keys.
+    fn is_held(&self, held: &HashSet<KeyCode>) -> bool {
+        self.keys.keys().all(|key| key.is_held(held))
+    }
+
     /// Adds an action to be emitted when all hotkeys are pressed.
     fn add_action(&mut self, action: &String) -> Result<()> {
         if self.actions.contains(action) {
-            return Err(Error::ActionAlreadyExists);
+            return Err(Error::ActionAlreadyExists {
+                action: action.clone(),
+                keys: keys_to_string(&self.sorted_keys()),
+            });
         }
 
         self.actions.push(action.clone());
@@ -89,10 +292,15 @@ impl ActionMapping {
     /// Removes an action to be emitted when all hotkeys are pressed.
     fn remove_action(&mut self, action: &String) -> Result<()> {
         if !self.actions.contains(action) {
-            return Err(Error::ActionDoesNotExist(MapType::ActionMapping));
+            return Err(Error::ActionDoesNotExist {
+                action: action.clone(),
+                keys: keys_to_string(&self.sorted_keys()),
+                map_type: MapType::Actions,
+            });
         }
 
         self.actions.retain(|a| a != action);
+        self.payloads.remove(action);
 
         Ok(())
     }
@@ -101,89 +309,190 @@ impl ActionMapping {
 /// Listens for hotkeys being pressed. If a registered sequence of keys is pressed within a minimum amount of time,
 /// then the actions associated with the key sequence is emitted.
 pub struct HotkeyListener {
-    hook: Hook,
+    backend: Box<dyn Backend>,
+
+    actions: HashMap<KeySet, ActionMapping>,
+    reverse_lookup: HashMap<KeyCode, Vec<KeySet>>,
+
+    sequences: HashMap<Vec<KeySet>, SequenceMapping>,
+    sequence_lookup: HashMap<KeyCode, Vec<Vec<KeySet>>>,
+
+    gestures: HashMap<KeySet, GestureMapping>,
+    gesture_lookup: HashMap<KeyCode, Vec<KeySet>>,
 
-    actions: HashMap<u64, ActionMapping>,
-    reverse_lookup: HashMap<KeyCode, Vec<u64>>,
+    layers: HashMap<String, Layer>,
+    /// Active layers, the top layer last. A layer is on the stack at most once.
+    layer_stack: Vec<ActiveLayer>,
 
     min_elapsed_time: Duration,
+    sequence_timeout: Duration,
+    max_events_per_poll: Option<usize>,
+    chord_mode: ChordMode,
+    resolution_policy: ResolutionPolicy,
+    edge_triggered: bool,
+    /// Incremented every time a new key sequence is registered.
+    next_registration: u64,
+    held_keys: HashSet<KeyCode>,
+    /// Keys currently registered with the backend.
+    hooked_keys: HashSet<KeyCode>,
+    /// Whether every key in `ALL_KEY_CODES` is hooked, not just the ones used by bindings.
+    capture_all: bool,
+    /// Keys the backend refused to hook in capture-all mode. They are not retried until capture-all mode
+    /// is disabled.
+    unavailable_keys: HashSet<KeyCode>,
+
+    /// Set while a capture session is running. See `start_capture`.
+    capture: Option<CaptureSession>,
+    /// The chord recorded by the last capture session, until it is taken.
+    captured: Option<Vec<KeyCode>>,
+
+    /// Bindings for every profile except the active one. The active profile's bindings live in `actions`
+    /// and `sequences`.
+    profiles: BTreeMap<String, Bindings>,
+    active_profile: String,
+
+    callback_sender: Sender<KeyEvent>,
+    callback_receiver: Receiver<KeyEvent>,
+    /// Receives a copy of every raw key event when set.
+    key_sender: Option<Sender<KeyEvent>>,
+
+    emitter: EventEmitter,
+}
+
+/// Every binding of the active profile along with its state, see `HotkeyListener::take_bindings`.
+struct BindingTables {
+    actions: HashMap<KeySet, ActionMapping>,
+    reverse_lookup: HashMap<KeyCode, Vec<KeySet>>,
+    sequences: HashMap<Vec<KeySet>, SequenceMapping>,
+    sequence_lookup: HashMap<KeyCode, Vec<Vec<KeySet>>>,
+    gestures: HashMap<KeySet, GestureMapping>,
+    gesture_lookup: HashMap<KeyCode, Vec<KeySet>>,
+}
 
-    callback_sender: Sender<KeyCode>,
-    callback_receiver: Receiver<KeyCode>,
+impl BindingTables {
+    /// Emits a release for every active key sequence and a reset for every partially completed ordered
+    /// sequence, as if their keys had been released or the sequences had expired at `time`.
+    fn release(&mut self, emitter: &mut EventEmitter, time: Instant) {
+        for am in self.actions.values_mut().filter(|am| am.is_active) {
+            am.is_active = false;
+            am.next_repeat = None;
+            emitter.emit_with_payloads(
+                &am.actions,
+                &am.payloads,
+                &am.sorted_keys(),
+                time,
+                ActionEventKind::Released,
+            );
+        }
 
-    listener_sender: Sender<String>,
+        for sm in self.sequences.values_mut().filter(|sm| sm.progress() > 0) {
+            sm.reset();
+            emitter.emit_with_payloads(
+                &sm.actions,
+                &sm.payloads,
+                &sm.unique_keys(),
+                time,
+                ActionEventKind::SequenceReset,
+            );
+        }
+    }
 }
 
 impl HotkeyListener {
-    /// Creates a new instance of `HotkeyListener`. This operation _can_ fail.
-    pub fn new(listener_sender: Sender<String>) -> Result<Self> {
-        let hook = match Hook::new() {
-            Ok(h) => h,
-            Err(e) => {
-                eprintln!("{e}");
-                return Err(Error::HookCreate);
-            }
-        };
+    /// Creates a new instance of `HotkeyListener` backed by an OS hook. This operation _can_ fail.
+    pub fn new(listener_sender: Sender<ActionEvent>) -> Result<Self> {
+        let backend = LivesplitBackend::new()?;
+
+        Ok(HotkeyListener::with_backend(
+            listener_sender,
+            Box::new(backend),
+        ))
+    }
 
-        let (sender, receiver) = unbounded::<KeyCode>();
+    /// Creates a new instance of `HotkeyListener` that receives key presses from the given `Backend`.
+    pub fn with_backend(listener_sender: Sender<ActionEvent>, backend: Box<dyn Backend>) -> Self {
+        let (sender, receiver) = unbounded::<KeyEvent>();
 
-        Ok(HotkeyListener {
-            hook: hook,
+        HotkeyListener {
+            backend,
 
             actions: HashMap::new(),
             reverse_lookup: HashMap::new(),
 
+            sequences: HashMap::new(),
+            sequence_lookup: HashMap::new(),
+
+            gestures: HashMap::new(),
+            gesture_lookup: HashMap::new(),
+
+            layers: HashMap::new(),
+            layer_stack: vec![],
+
             min_elapsed_time: Duration::from_secs_f32(0.2), // TODO hardcoded value?
+            sequence_timeout: Duration::from_secs(1),
+            max_events_per_poll: None,
+            chord_mode: ChordMode::Timed,
+            resolution_policy: ResolutionPolicy::FireAll,
+            edge_triggered: false,
+            next_registration: 0,
+            held_keys: HashSet::new(),
+            hooked_keys: HashSet::new(),
+            capture_all: false,
+            unavailable_keys: HashSet::new(),
+
+            capture: None,
+            captured: None,
+
+            profiles: BTreeMap::new(),
+            active_profile: DEFAULT_PROFILE.to_string(),
 
             callback_sender: sender,
             callback_receiver: receiver,
+            key_sender: None,
 
-            listener_sender: listener_sender,
-        })
+            emitter: EventEmitter::new(listener_sender),
+        }
     }
 
-    /// Registers an action by name and key sequence. The key sequence is hashed and that hash is used to store
-    /// action names.
+    /// Registers an action by name and key sequence. The key sequence is identified by its `KeySet`, so the
+    /// order of `keys` does not matter.
     ///
     /// For every key associated with the action, a reverse lookup is used (key -> action) for quick access.
+    ///
+    /// Keys are named after `KeyCode` variants, e.g. `ControlLeft`. `Control`, `Shift`, `Alt` and `Super`
+    /// match either the left or the right key, see `Key`.
     pub fn register_action(&mut self, action_name: &String, keys: &[String]) -> Result<()> {
-        let (key_codes, key_codes_hash) = match string_slice_to_vec_and_hash(keys) {
-            Ok(v) => v,
-            Err(e) => return Err(e),
-        };
+        self.add_action_mapping(action_name, keys)?;
+
+        self.sync_hooks()
+    }
+
+    /// Bookkeeping for `register_action`. Does not hook any keys.
+    fn add_action_mapping(&mut self, action_name: &String, keys: &[String]) -> Result<()> {
+        let (key_codes, key_set) = string_slice_to_vec_and_key_set(keys)?;
 
-        match self.actions.get_mut(&key_codes_hash) {
+        match self.actions.get_mut(&key_set) {
             Some(am) => match am.add_action(action_name) {
                 Ok(_) => {}
                 Err(e) => return Err(e),
             },
             None => {
-                let mut am = ActionMapping::new(key_codes.as_slice());
-                am.add_action(action_name).unwrap();
-                self.actions.insert(key_codes_hash, am);
+                let mut am = ActionMapping::new(key_codes.as_slice(), self.next_registration);
+                self.next_registration += 1;
+                am.add_action(action_name)?;
+                self.actions.insert(key_set.clone(), am);
             }
         }
 
-        for key in key_codes.iter() {
-            match self.reverse_lookup.get_mut(key) {
+        for key in key_codes.iter().flat_map(Key::key_codes) {
+            match self.reverse_lookup.get_mut(&key) {
                 Some(v) => {
-                    if !v.contains(&key_codes_hash) {
-                        v.push(key_codes_hash);
+                    if !v.contains(&key_set) {
+                        v.push(key_set.clone());
                     }
                 }
                 None => {
-                    let sender = self.callback_sender.clone();
-                    let key = key.clone();
-                    match self.hook.register(key, move || match sender.send(key) {
-                        Ok(_) => {}
-                        Err(e) => eprintln!("{e}"),
-                    }) {
-                        Ok(_) => {}
-                        Err(e) => {
-                            return Err(Error::CannotRegisterHotkey(e));
-                        }
-                    }
-                    self.reverse_lookup.insert(key, vec![key_codes_hash]);
+                    self.reverse_lookup.insert(key, vec![key_set.clone()]);
                 }
             }
         }
@@ -194,165 +503,2135 @@ impl HotkeyListener {
     /// Safely removes an action + key sequence without accidentally removing other action's hotkeys.
     /// If no more actions depend on a certain key, the hook for that key is unregistered.
     pub fn unregister_action(&mut self, action_name: &String, keys: &[String]) -> Result<()> {
-        let (key_codes, key_codes_hash) = match string_slice_to_vec_and_hash(keys) {
-            Ok(v) => v,
-            Err(e) => return Err(e),
-        };
+        self.remove_action_mapping(action_name, keys)?;
 
-        let mut is_empty_hash = false;
+        self.sync_hooks()
+    }
+
+    /// Bookkeeping for `unregister_action`. Does not unhook any keys.
+    fn remove_action_mapping(&mut self, action_name: &String, keys: &[String]) -> Result<()> {
+        let (key_codes, key_set) = string_slice_to_vec_and_key_set(keys)?;
 
-        match self.actions.get_mut(&key_codes_hash) {
+        let mut is_empty_key_set = false;
+
+        match self.actions.get_mut(&key_set) {
             Some(am) => match am.remove_action(action_name) {
                 Ok(_) => {
-                    if am.actions.len() < 1 {
-                        is_empty_hash = true;
+                    if am.actions.is_empty() {
+                        is_empty_key_set = true;
                     }
                 }
                 Err(e) => return Err(e),
             },
-            None => return Err(Error::ActionDoesNotExist(MapType::Actions)),
+            None => {
+                return Err(Error::ActionDoesNotExist {
+                    action: action_name.clone(),
+                    keys: keys_to_string(&key_codes),
+                    map_type: MapType::Actions,
+                })
+            }
         }
 
-        if !is_empty_hash {
+        if !is_empty_key_set {
             return Ok(());
         }
 
         let mut empty_keys: Vec<KeyCode> = vec![];
+        let mut missing_key = None;
 
-        match self.actions.remove(&key_codes_hash) {
-            Some(_) => {}
-            None => unreachable!(),
-        }
+        self.actions.remove(&key_set);
 
-        for key in key_codes.iter() {
-            match self.reverse_lookup.get_mut(key) {
+        for key in key_codes.iter().flat_map(Key::key_codes) {
+            match self.reverse_lookup.get_mut(&key) {
                 Some(v) => {
-                    v.retain(|hash| hash != &key_codes_hash);
-                    if v.is_empty() {
-                        empty_keys.push(*key);
+                    v.retain(|k| k != &key_set);
+                    if v.is_empty() && !empty_keys.contains(&key) {
+                        empty_keys.push(key);
                     }
                 }
-                None => unreachable!(),
+                None => missing_key = Some(key),
             }
         }
 
         for key in empty_keys.iter() {
-            match self.reverse_lookup.remove(key) {
-                Some(_) => match self.hook.unregister(*key) {
-                    Ok(_) => {}
-                    Err(e) => return Err(Error::CannotUnregisterHotkey(e)),
-                },
-                None => unreachable!(),
+            self.reverse_lookup.remove(key);
+        }
+
+        match missing_key {
+            Some(key) => Err(Error::MappedKeyMissingInReverseLookup(key)),
+            None => Ok(()),
+        }
+    }
+
+    /// Registers an action by name and an ordered list of key sequences, e.g. `Ctrl+K` followed by `Ctrl+S`.
+    /// The action is emitted once every step has been pressed in order, with no more than the sequence timeout
+    /// between steps. Keys _within_ a step may still be pressed in any order.
+    ///
+    /// Every hooked key is considered when matching, so pressing any other bound key resets the sequence.
+    pub fn register_sequence(&mut self, action_name: &String, steps: &[Vec<String>]) -> Result<()> {
+        self.add_sequence_mapping(action_name, steps)?;
+
+        self.sync_hooks()
+    }
+
+    /// Bookkeeping for `register_sequence`. Does not hook any keys.
+    fn add_sequence_mapping(&mut self, action_name: &String, steps: &[Vec<String>]) -> Result<()> {
+        let (steps, key_sets) = string_steps_to_vec_and_key_sets(steps)?;
+
+        match self.sequences.get_mut(&key_sets) {
+            Some(sm) => sm.add_action(action_name)?,
+            None => {
+                let mut sm = SequenceMapping::new(steps.clone());
+                sm.add_action(action_name)?;
+                self.sequences.insert(key_sets.clone(), sm);
+            }
+        }
+
+        for key in steps.iter().flatten().flat_map(Key::key_codes) {
+            match self.sequence_lookup.get_mut(&key) {
+                Some(v) => {
+                    if !v.contains(&key_sets) {
+                        v.push(key_sets.clone());
+                    }
+                }
+                None => {
+                    self.sequence_lookup.insert(key, vec![key_sets.clone()]);
+                }
             }
         }
 
         Ok(())
     }
 
-    // TODO maybe we should clear the channel? Clearing the channel might infinitely loop though
-    /// Checks if any actions have been triggered. Needs to be polled at regular intervals
-    /// or else the receivers might grow infinitely large or the senders might block infinitely.
-    pub fn poll(&mut self) {
-        if self.callback_receiver.is_empty() {
-            return;
+    /// Safely removes an action + ordered list of key sequences. Mirrors `unregister_action`.
+    pub fn unregister_sequence(
+        &mut self,
+        action_name: &String,
+        steps: &[Vec<String>],
+    ) -> Result<()> {
+        self.remove_sequence_mapping(action_name, steps)?;
+
+        self.sync_hooks()
+    }
+
+    /// Bookkeeping for `unregister_sequence`. Does not unhook any keys.
+    fn remove_sequence_mapping(
+        &mut self,
+        action_name: &String,
+        steps: &[Vec<String>],
+    ) -> Result<()> {
+        let (key_steps, key_sets) = string_steps_to_vec_and_key_sets(steps)?;
+
+        let sm = match self.sequences.get_mut(&key_sets) {
+            Some(sm) => sm,
+            None => {
+                return Err(Error::ActionDoesNotExist {
+                    action: action_name.clone(),
+                    keys: steps_to_string(&key_steps),
+                    map_type: MapType::Sequences,
+                })
+            }
+        };
+        sm.remove_action(action_name)?;
+        if !sm.actions.is_empty() {
+            return Ok(());
         }
 
-        match self.callback_receiver.recv() {
-            Ok(key) => {
-                if !self.reverse_lookup.contains_key(&key) {
-                    return;
-                }
+        self.sequences.remove(&key_sets);
 
-                let vec = match self.reverse_lookup.get(&key) {
-                    Some(v) => v,
-                    None => {
-                        return;
-                    }
-                };
+        let mut empty_keys: Vec<KeyCode> = vec![];
+        let mut missing_key = None;
 
-                for hash in vec.iter() {
-                    match self.actions.get_mut(&hash) {
-                        Some(am) => {
-                            am.press_key(&key);
-                            if am.is_pressed(&self.min_elapsed_time) {
-                                for action_name in am.actions.iter() {
-                                    match self.listener_sender.send(action_name.clone()) {
-                                        Ok(_) => {}
-                                        Err(e) => eprintln!("{e}"),
-                                    }
-                                }
-                            }
-                        }
-                        None => unreachable!(),
+        for key in key_steps.iter().flatten().flat_map(Key::key_codes) {
+            match self.sequence_lookup.get_mut(&key) {
+                Some(v) => {
+                    v.retain(|k| k != &key_sets);
+                    if v.is_empty() && !empty_keys.contains(&key) {
+                        empty_keys.push(key);
                     }
                 }
+                None => missing_key = Some(key),
             }
-            Err(e) => eprintln!("{e}"),
+        }
+
+        for key in empty_keys.iter() {
+            self.sequence_lookup.remove(key);
+        }
+
+        match missing_key {
+            Some(key) => Err(Error::MappedKeyMissingInReverseLookup(key)),
+            None => Ok(()),
         }
     }
 
-    /// Returns the minimum elapsed time as an `f32` in seconds.
-    pub fn get_min_elapsed_time(&self) -> f32 {
-        self.min_elapsed_time.as_secs_f32()
+    /// Registers an action from a hotkey string like `Ctrl+S` or `Ctrl+K, Ctrl+S`, see `parse_hotkey`.
+    /// A single chord is registered with `register_action` and several with `register_sequence`.
+    pub fn register_hotkey(&mut self, action_name: &String, hotkey: &str) -> Result<()> {
+        let steps = parse_hotkey(hotkey)?;
+
+        match steps.as_slice() {
+            [keys] => self.register_action(action_name, keys),
+            _ => self.register_sequence(action_name, &steps),
+        }
     }
 
-    /// Converts an `f32` into a `Duration`. Treats the `f32` as seconds.
-    pub fn set_min_elapsed_time(&mut self, min_elapsed_time: f32) {
-        self.min_elapsed_time = Duration::from_secs_f32(min_elapsed_time);
+    /// Removes an action registered with `register_hotkey`.
+    pub fn unregister_hotkey(&mut self, action_name: &String, hotkey: &str) -> Result<()> {
+        let steps = parse_hotkey(hotkey)?;
+
+        match steps.as_slice() {
+            [keys] => self.unregister_action(action_name, keys),
+            _ => self.unregister_sequence(action_name, &steps),
+        }
     }
 
-    /// Iterates through all actions and returns a non-repeating `Vec` of all registered actions.
+    /// Registers an action to be emitted when a set of keys is tapped, double tapped or long pressed.
     ///
-    /// The `Vec` is initially unsorted but is sorted in order to remove duplicates.
-    pub fn get_action_names(&self) -> Vec<String> {
-        let mut r = self
-            .actions
-            .values()
-            .into_iter()
-            .flat_map(|am| am.actions.clone())
-            .collect::<Vec<String>>();
+    /// Several gestures can share the same keys, e.g. a tap and a long press on `F1`. Gestures are
+    /// independent of actions registered with `register_action` on the same keys.
+    ///
+    /// Refused if the `Backend` does not report key releases, since no gesture could ever be told apart.
+    pub fn register_gesture(
+        &mut self,
+        action_name: &String,
+        keys: &[String],
+        gesture: Gesture,
+    ) -> Result<()> {
+        self.add_gesture_mapping(action_name, keys, gesture)?;
 
-        r.sort_unstable();
-        r.dedup();
+        self.sync_hooks()
+    }
 
-        r
+    /// Bookkeeping for `register_gesture`. Does not hook any keys.
+    fn add_gesture_mapping(
+        &mut self,
+        action_name: &String,
+        keys: &[String],
+        gesture: Gesture,
+    ) -> Result<()> {
+        let (key_codes, key_set) = string_slice_to_vec_and_key_set(keys)?;
+        gesture.validate()?;
+        if !self.backend.reports_releases() {
+            return Err(Error::ReleasesNotReported("a gesture"));
+        }
+
+        match self.gestures.get_mut(&key_set) {
+            Some(gm) => gm.add_action(gesture, action_name)?,
+            None => {
+                let mut gm = GestureMapping::new(key_codes.clone());
+                gm.add_action(gesture, action_name)?;
+                self.gestures.insert(key_set.clone(), gm);
+            }
+        }
+
+        for key in key_codes.iter().flat_map(Key::key_codes) {
+            match self.gesture_lookup.get_mut(&key) {
+                Some(v) => {
+                    if !v.contains(&key_set) {
+                        v.push(key_set.clone());
+                    }
+                }
+                None => {
+                    self.gesture_lookup.insert(key, vec![key_set.clone()]);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Safely removes an action from a gesture. Only the kind of the gesture needs to match, not its time.
+    pub fn unregister_gesture(
+        &mut self,
+        action_name: &String,
+        keys: &[String],
+        gesture: Gesture,
+    ) -> Result<()> {
+        self.remove_gesture_mapping(action_name, keys, gesture)?;
+
+        self.sync_hooks()
+    }
+
+    /// Bookkeeping for `unregister_gesture`. Does not unhook any keys.
+    fn remove_gesture_mapping(
+        &mut self,
+        action_name: &String,
+        keys: &[String],
+        gesture: Gesture,
+    ) -> Result<()> {
+        let (key_codes, key_set) = string_slice_to_vec_and_key_set(keys)?;
+
+        let gm = match self.gestures.get_mut(&key_set) {
+            Some(gm) => gm,
+            None => {
+                return Err(Error::ActionDoesNotExist {
+                    action: action_name.clone(),
+                    keys: format!("{} ({})", keys_to_string(&key_codes), gesture.as_str()),
+                    map_type: MapType::Gestures,
+                })
+            }
+        };
+        gm.remove_action(gesture, action_name)?;
+        if !gm.is_empty() {
+            return Ok(());
+        }
+
+        self.gestures.remove(&key_set);
+
+        let mut empty_keys: Vec<KeyCode> = vec![];
+        let mut missing_key = None;
+
+        for key in key_codes.iter().flat_map(Key::key_codes) {
+            match self.gesture_lookup.get_mut(&key) {
+                Some(v) => {
+                    v.retain(|k| k != &key_set);
+                    if v.is_empty() && !empty_keys.contains(&key) {
+                        empty_keys.push(key);
+                    }
+                }
+                None => missing_key = Some(key),
+            }
+        }
+
+        for key in empty_keys.iter() {
+            self.gesture_lookup.remove(key);
+        }
+
+        match missing_key {
+            Some(key) => Err(Error::MappedKeyMissingInReverseLookup(key)),
+            None => Ok(()),
+        }
     }
 
-    /// Iterates through all reverse lookup keys and returns their names as a `Vec`.
+    /// Registers a layer: a table of key sequences that only apply while the layer is active, like the prefix
+    /// key in tmux. Layers are entered by pressing the `leader` key sequence or with `push_layer`. An empty
+    /// `leader` means the layer can only be entered with `push_layer`.
     ///
-    /// Names are _not_ sorted.
-    pub fn get_key_names(&self) -> Vec<String> {
-        self.reverse_lookup
-            .keys()
-            .into_iter()
-            .map(|k| k.as_str().to_string())
-            .collect::<Vec<String>>()
+    /// While a layer is active, its keys are hooked and handled by the layer instead of the regular bindings.
+    /// Escape exits the top layer unless the layer binds it. A layer with a `timeout` (in seconds) exits on its
+    /// own once no key press has been handled by a layer for that long.
+    ///
+    /// Layers are not part of profiles or exported bindings.
+    pub fn register_layer(
+        &mut self,
+        name: &String,
+        leader: &[String],
+        timeout: Option<f32>,
+    ) -> Result<()> {
+        if self.layers.contains_key(name) {
+            return Err(Error::LayerAlreadyExists(name.clone()));
+        }
+
+        let leader = if leader.is_empty() {
+            None
+        } else {
+            let (keys, _) = string_slice_to_vec_and_key_set(leader)?;
+            self.next_registration += 1;
+            Some(ActionMapping::new(
+                keys.as_slice(),
+                self.next_registration - 1,
+            ))
+        };
+        let timeout = match timeout {
+            Some(timeout) => Some(secs_to_duration(timeout, "layer timeout")?),
+            None => None,
+        };
+
+        self.layers
+            .insert(name.clone(), Layer::new(leader, timeout));
+
+        self.sync_hooks()
     }
-}
 
-/// Converts a `String` slice to a `Vec<String>` and then takes the hash of that `Vec`.
-/// Sorts the keys beforehand to ensure ordering doesn't impact the hash.
-fn string_slice_to_vec_and_hash(keys: &[String]) -> Result<(Vec<KeyCode>, u64)> {
-    let mut keys = keys.to_vec();
-    keys.sort();
-
-    let mut key_codes = vec![];
-    for key in keys.iter() {
-        match KeyCode::from_str(key) {
-            Ok(k) => key_codes.push(k),
-            Err(_) => return Err(Error::BadKeyCodeName),
+    /// Removes a layer along with all of its key sequences, exiting it first if it is active.
+    pub fn unregister_layer(&mut self, name: &String) -> Result<()> {
+        if !self.layers.contains_key(name) {
+            return Err(Error::LayerDoesNotExist(name.clone()));
+        }
+
+        if let Some(index) = self.layer_stack.iter().position(|a| &a.name == name) {
+            self.exit_layer(index, Instant::now());
+        }
+        self.layers.remove(name);
+
+        self.sync_hooks()
+    }
+
+    /// Registers an action that is emitted when a set of keys is pressed while the layer is the top-most active
+    /// layer binding any of the keys. See `register_action`.
+    pub fn register_layer_action(
+        &mut self,
+        layer_name: &String,
+        action_name: &String,
+        keys: &[String],
+    ) -> Result<()> {
+        let (key_codes, key_set) = string_slice_to_vec_and_key_set(keys)?;
+
+        let layer = match self.layers.get_mut(layer_name) {
+            Some(layer) => layer,
+            None => return Err(Error::LayerDoesNotExist(layer_name.clone())),
         };
+        layer.add_action(
+            action_name,
+            key_codes.as_slice(),
+            &key_set,
+            self.next_registration,
+        )?;
+        self.next_registration += 1;
+
+        self.sync_hooks()
     }
 
-    let key_codes_hash = get_hash(&key_codes);
+    /// Removes an action registered with `register_layer_action`.
+    pub fn unregister_layer_action(
+        &mut self,
+        layer_name: &String,
+        action_name: &String,
+        keys: &[String],
+    ) -> Result<()> {
+        let (key_codes, key_set) = string_slice_to_vec_and_key_set(keys)?;
 
-    Ok((key_codes, key_codes_hash))
-}
+        let layer = match self.layers.get_mut(layer_name) {
+            Some(layer) => layer,
+            None => return Err(Error::LayerDoesNotExist(layer_name.clone())),
+        };
+        layer.remove_action(action_name, key_codes.as_slice(), &key_set)?;
+
+        self.sync_hooks()
+    }
+
+    /// Activates a layer on top of every other active layer and hooks its keys. Pushing a layer that is
+    /// already active only restarts its timeout.
+    pub fn push_layer(&mut self, name: &String) -> Result<()> {
+        if !self.layers.contains_key(name) {
+            return Err(Error::LayerDoesNotExist(name.clone()));
+        }
+
+        self.enter_layer(name, &[], Instant::now());
+
+        self.sync_hooks()
+    }
+
+    /// Exits the top-most active layer and unhooks the keys that are no longer needed. Returns the name of
+    /// the layer, or `None` if no layer is active.
+    pub fn pop_layer(&mut self) -> Result<Option<String>> {
+        if self.layer_stack.is_empty() {
+            return Ok(None);
+        }
+
+        let name = self.exit_layer(self.layer_stack.len() - 1, Instant::now());
+        self.sync_hooks()?;
+
+        Ok(Some(name))
+    }
+
+    /// Returns the name of every active layer, the top-most layer last.
+    pub fn get_layer_stack(&self) -> Vec<String> {
+        self.layer_stack.iter().map(|a| a.name.clone()).collect()
+    }
+
+    /// Returns the name of every registered layer, sorted.
+    pub fn get_layer_names(&self) -> Vec<String> {
+        let mut r = self.layers.keys().cloned().collect::<Vec<String>>();
+        r.sort_unstable();
+
+        r
+    }
+
+    /// Pushes a layer and emits `ActionEventKind::LayerEntered`, or restarts its timeout if it is already
+    /// active. Does not hook any keys.
+    fn enter_layer(&mut self, name: &String, leader: &[Key], time: Instant) {
+        if let Some(active) = self.layer_stack.iter_mut().find(|a| &a.name == name) {
+            active.last_activity = time;
+            return;
+        }
+
+        self.layer_stack.push(ActiveLayer {
+            name: name.clone(),
+            last_activity: time,
+        });
+        self.emitter.emit(
+            std::slice::from_ref(name),
+            leader,
+            time,
+            ActionEventKind::LayerEntered,
+        );
+    }
+
+    /// Removes a layer from the stack, releases its active key sequences and emits
+    /// `ActionEventKind::LayerExited`. Does not unhook any keys. Returns the name of the layer.
+    fn exit_layer(&mut self, index: usize, time: Instant) -> String {
+        let active = self.layer_stack.remove(index);
+
+        if let Some(layer) = self.layers.get_mut(&active.name) {
+            for am in layer.actions.values_mut().filter(|am| am.is_active) {
+                am.is_active = false;
+                self.emitter.emit_with_payloads(
+                    &am.actions,
+                    &am.payloads,
+                    &am.sorted_keys(),
+                    time,
+                    ActionEventKind::Released,
+                );
+            }
+        }
+        self.emitter.emit(
+            std::slice::from_ref(&active.name),
+            &[],
+            time,
+            ActionEventKind::LayerExited,
+        );
+
+        active.name
+    }
+
+    /// When an active layer times out. `None` if the layer has no timeout.
+    fn layer_deadline(&self, active: &ActiveLayer) -> Option<Instant> {
+        self.layers
+            .get(&active.name)
+            .and_then(|layer| layer.timeout)
+            .map(|timeout| active.last_activity + timeout)
+    }
 
-/// Gets the hash of some data using a new hasher.
-fn get_hash<T: Hash>(data: &T) -> u64 {
-    let mut hasher = DefaultHasher::new();
-    data.hash(&mut hasher);
+    /// Exits every active layer whose timeout has passed by `now`.
+    fn expire_layers(&mut self, now: Instant) {
+        let mut is_changed = false;
+        for index in (0..self.layer_stack.len()).rev() {
+            match self.layer_deadline(&self.layer_stack[index]) {
+                Some(deadline) if deadline <= now => {
+                    self.exit_layer(index, deadline);
+                    is_changed = true;
+                }
+                _ => {}
+            }
+        }
+
+        if is_changed {
+            match self.sync_hooks() {
+                Ok(_) => {}
+                Err(e) => eprintln!("{e}"),
+            }
+        }
+    }
+
+    /// Checks if `key` needs to be handled because of a layer, i.e. it is part of a leader, bound by an active
+    /// layer or exits the top layer.
+    fn is_layer_key(&self, key: &KeyCode) -> bool {
+        if *key == KeyCode::Escape && !self.layer_stack.is_empty() {
+            return true;
+        }
+
+        self.layers.values().any(|layer| layer.is_leader_key(key))
+            || self.layer_stack.iter().any(|a| {
+                self.layers
+                    .get(&a.name)
+                    .is_some_and(|layer| layer.lookup.contains_key(key))
+            })
+    }
+
+    /// Every key that needs to be hooked because of a layer. See `is_layer_key`.
+    fn layer_keys(&self) -> HashSet<KeyCode> {
+        let mut r = self
+            .layers
+            .values()
+            .filter_map(|layer| layer.leader.as_ref())
+            .flat_map(|am| am.keys.keys().flat_map(Key::key_codes))
+            .collect::<HashSet<KeyCode>>();
+
+        for a in self.layer_stack.iter() {
+            if let Some(layer) = self.layers.get(&a.name) {
+                r.extend(layer.lookup.keys());
+            }
+        }
+        if !self.layer_stack.is_empty() {
+            r.insert(KeyCode::Escape);
+        }
+
+        r
+    }
+
+    /// Lets the top-most active layer binding the key handle a key press. Returns `true` if the key press was
+    /// handled by a layer and must not reach the regular bindings.
+    fn press_layer_key(&mut self, key: KeyCode, time: Instant, is_os_repeat: bool) -> bool {
+        let name = match self.layer_stack.iter().rev().find(|a| {
+            self.layers
+                .get(&a.name)
+                .is_some_and(|layer| layer.lookup.contains_key(&key))
+        }) {
+            Some(a) => a.name.clone(),
+            None => {
+                if key != KeyCode::Escape || self.layer_stack.is_empty() {
+                    return false;
+                }
+                if !is_os_repeat {
+                    self.exit_layer(self.layer_stack.len() - 1, time);
+                    match self.sync_hooks() {
+                        Ok(_) => {}
+                        Err(e) => eprintln!("{e}"),
+                    }
+                }
+                return true;
+            }
+        };
+
+        for a in self.layer_stack.iter_mut() {
+            a.last_activity = time;
+        }
+
+        // The lookups and the layer stack are kept in sync with the tables, see `validate`. Anything missing
+        // is skipped instead of panicking.
+        let layer = match self.layers.get_mut(&name) {
+            Some(layer) => layer,
+            None => return false,
+        };
+        let mut completed = vec![];
+        for key_set in layer.lookup.get(&key).into_iter().flatten() {
+            match layer.actions.get_mut(key_set) {
+                Some(am) => {
+                    if am.is_active && (self.edge_triggered || is_os_repeat) {
+                        continue;
+                    }
+                    if am.press(
+                        &key,
+                        time,
+                        self.chord_mode,
+                        &self.min_elapsed_time,
+                        &self.held_keys,
+                    ) {
+                        completed.push(key_set.clone());
+                    }
+                }
+                None => continue,
+            }
+        }
+
+        let layer = match self.layers.get(&name) {
+            Some(layer) => layer,
+            None => return false,
+        };
+        let winners = self.resolve(&layer.actions, completed);
+
+        let layer = match self.layers.get_mut(&name) {
+            Some(layer) => layer,
+            None => return false,
+        };
+        for key_set in winners {
+            match layer.actions.get_mut(&key_set) {
+                Some(am) => {
+                    am.is_active = true;
+                    self.emitter.emit_with_payloads(
+                        &am.actions,
+                        &am.payloads,
+                        &am.sorted_keys(),
+                        time,
+                        ActionEventKind::Pressed,
+                    );
+                }
+                None => continue,
+            }
+        }
+
+        true
+    }
+
+    /// Enters every layer whose leader is now pressed. OS repeats of a held leader are ignored, which can only
+    /// be told apart from new presses if the `Backend` reports releases.
+    fn press_leaders(&mut self, key: KeyCode, time: Instant, is_os_repeat: bool) {
+        if is_os_repeat {
+            return;
+        }
+
+        let mut entered = vec![];
+        for (name, layer) in self.layers.iter_mut() {
+            let leader = match layer.leader.as_mut() {
+                Some(am) if am.keys.keys().any(|k| k.matches(&key)) => am,
+                _ => continue,
+            };
+            if leader.press(
+                &key,
+                time,
+                self.chord_mode,
+                &self.min_elapsed_time,
+                &self.held_keys,
+            ) {
+                entered.push((name.clone(), leader.sorted_keys()));
+            }
+        }
+        if entered.is_empty() {
+            return;
+        }
+
+        entered.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        for (name, leader) in entered.iter() {
+            self.enter_layer(name, leader, time);
+        }
+        match self.sync_hooks() {
+            Ok(_) => {}
+            Err(e) => eprintln!("{e}"),
+        }
+    }
+
+    /// Emits a release for every active key sequence of an active layer containing the key.
+    fn release_layer_key(&mut self, key: KeyCode, time: Instant) {
+        for a in self.layer_stack.iter() {
+            let layer = match self.layers.get_mut(&a.name) {
+                Some(layer) => layer,
+                None => continue,
+            };
+            for key_set in layer.lookup.get(&key).into_iter().flatten() {
+                match layer.actions.get_mut(key_set) {
+                    Some(am) => {
+                        if !am.is_active || am.is_held(&self.held_keys) {
+                            continue;
+                        }
+                        am.is_active = false;
+                        self.emitter.emit_with_payloads(
+                            &am.actions,
+                            &am.payloads,
+                            &am.sorted_keys(),
+                            time,
+                            ActionEventKind::Released,
+                        );
+                    }
+                    None => continue,
+                }
+            }
+        }
+    }
+
+    /// Returns every registered action and its key sequences as a serializable document.
+    pub fn export_bindings(&self) -> Bindings {
+        let mut bindings = Bindings::default();
+
+        // Registration numbers are shared with layers, so only the rank among key sets is exported
+        let mut registrations = self
+            .actions
+            .values()
+            .map(|am| am.registration)
+            .collect::<Vec<u64>>();
+        registrations.sort_unstable();
+
+        for am in self.actions.values() {
+            let order = registrations
+                .binary_search(&am.registration)
+                .ok()
+                .map(|i| i as u64);
+            let keys = am
+                .sorted_keys()
+                .iter()
+                .map(Key::name)
+                .collect::<Vec<String>>();
+            for action_name in am.actions.iter() {
+                bindings
+                    .actions
+                    .entry(action_name.clone())
+                    .or_default()
+                    .push(Binding {
+                        keys: keys.clone(),
+                        priority: am.priority,
+                        order,
+                        min_elapsed_time: am.min_elapsed_time.map(|d| d.as_secs_f32()),
+                        repeat: am.repeat.map(|(delay, interval)| AutoRepeat {
+                            delay: delay.as_secs_f32(),
+                            interval: interval.as_secs_f32(),
+                        }),
+                        payload: am.payloads.get(action_name).cloned(),
+                        ..Default::default()
+                    });
+            }
+        }
+
+        for sm in self.sequences.values() {
+            let steps = sm
+                .steps
+                .iter()
+                .map(|step| step.iter().map(Key::name).collect())
+                .collect::<Vec<Vec<String>>>();
+            for action_name in sm.actions.iter() {
+                bindings
+                    .actions
+                    .entry(action_name.clone())
+                    .or_default()
+                    .push(Binding {
+                        steps: steps.clone(),
+                        payload: sm.payloads.get(action_name).cloned(),
+                        ..Default::default()
+                    });
+            }
+        }
+
+        for gm in self.gestures.values() {
+            let keys = gm.keys.iter().map(Key::name).collect::<Vec<String>>();
+            for entry in gm.entries.iter() {
+                for action_name in entry.actions.iter() {
+                    bindings
+                        .actions
+                        .entry(action_name.clone())
+                        .or_default()
+                        .push(Binding {
+                            keys: keys.clone(),
+                            gesture: Some(entry.gesture),
+                            payload: entry.payloads.get(action_name).cloned(),
+                            ..Default::default()
+                        });
+                }
+            }
+        }
+
+        for list in bindings.actions.values_mut() {
+            list.sort_by(|a, b| {
+                let a_gesture = a.gesture.map(|g| g.as_str());
+                let b_gesture = b.gesture.map(|g| g.as_str());
+                (&a.keys, &a.steps, a_gesture).cmp(&(&b.keys, &b.steps, b_gesture))
+            });
+        }
+
+        bindings
+    }
+
+    /// Replaces every registered action with the contents of `bindings`.
+    ///
+    /// The document is validated first. If any entry is invalid, nothing is changed and every invalid entry is
+    /// reported via `Error::InvalidBindings`. Only hooks for keys that are not shared between the old and new
+    /// bindings are registered or unregistered. If registering or hooking fails, the previous bindings are
+    /// restored as they were, including key sequences that are still held. If they cannot be hooked again
+    /// either, both errors are returned in `Error::RestoreFailed`.
+    ///
+    /// Once the import succeeded, the previously active key sequences are released and partially completed
+    /// ordered sequences are reset, so every `Pressed` event is still followed by a `Released` event.
+    pub fn import_bindings(&mut self, bindings: &Bindings) -> Result<()> {
+        if let Err(errors) = bindings.validate() {
+            return Err(Error::InvalidBindings(errors));
+        }
+
+        let mut previous = self.take_bindings();
+        let result = match self.add_bindings(bindings) {
+            Ok(_) => self.sync_hooks(),
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(_) => {
+                previous.release(&mut self.emitter, Instant::now());
+                Ok(())
+            }
+            Err(e) => {
+                self.put_bindings(previous);
+                match self.sync_hooks() {
+                    Ok(_) => Err(e),
+                    Err(restore_error) => Err(Error::RestoreFailed {
+                        error: Box::new(e),
+                        restore_error: Box::new(restore_error),
+                    }),
+                }
+            }
+        }
+    }
+
+    /// Writes every registered action to a TOML file at `path`.
+    pub fn save_bindings<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let document = self.export_bindings().to_toml()?;
+
+        match fs::write(path.as_ref(), document) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(Error::CannotAccessBindingsFile {
+                path: path.as_ref().to_path_buf(),
+                error: e,
+            }),
+        }
+    }
+
+    /// Reads a TOML file at `path` and imports it with `import_bindings`.
+    pub fn load_bindings<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let document = match fs::read_to_string(path.as_ref()) {
+            Ok(s) => s,
+            Err(e) => {
+                return Err(Error::CannotAccessBindingsFile {
+                    path: path.as_ref().to_path_buf(),
+                    error: e,
+                })
+            }
+        };
+
+        self.import_bindings(&Bindings::from_toml(&document)?)
+    }
+
+    /// Reports key sequences in the active profile that are bound to several actions, that are contained in
+    /// other key sequences, or that share some keys with other key sequences. Ordered sequences are not
+    /// considered.
+    pub fn find_conflicts(&self) -> Vec<Conflict> {
+        conflicts::find_conflicts(self.actions.values())
+    }
+
+    /// Checks that every lookup used to find bindings by key agrees with the bindings: every key of a key
+    /// sequence, ordered sequence, gesture or layer key sequence leads back to it, every lookup entry leads to
+    /// a binding using the key, and every active layer exists. Returns every inconsistency found.
+    ///
+    /// Inconsistencies indicate a bug. Key events that run into one are skipped instead of panicking.
+    pub fn validate(&self) -> std::result::Result<(), Vec<Error>> {
+        let mut errors = vec![];
+
+        validate_lookup(
+            &self.actions,
+            &self.reverse_lookup,
+            |am| am.keys.keys().flat_map(Key::key_codes).collect(),
+            &mut errors,
+        );
+        validate_lookup(
+            &self.sequences,
+            &self.sequence_lookup,
+            |sm| sm.keys().flat_map(Key::key_codes).collect(),
+            &mut errors,
+        );
+        validate_lookup(
+            &self.gestures,
+            &self.gesture_lookup,
+            |gm| gm.keys.iter().flat_map(Key::key_codes).collect(),
+            &mut errors,
+        );
+        for layer in self.layers.values() {
+            validate_lookup(
+                &layer.actions,
+                &layer.lookup,
+                |am| am.keys.keys().flat_map(Key::key_codes).collect(),
+                &mut errors,
+            );
+        }
+
+        for a in self.layer_stack.iter() {
+            if !self.layers.contains_key(&a.name) {
+                errors.push(Error::LayerDoesNotExist(a.name.clone()));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Returns the name of every profile, including the active one, sorted.
+    pub fn get_profiles(&self) -> Vec<String> {
+        let mut r = self
+            .profiles
+            .keys()
+            .chain(std::iter::once(&self.active_profile))
+            .cloned()
+            .collect::<Vec<String>>();
+
+        r.sort_unstable();
+
+        r
+    }
+
+    /// Returns the name of the profile that `register_action` and friends currently operate on.
+    pub fn get_active_profile(&self) -> &String {
+        &self.active_profile
+    }
+
+    /// Adds a new profile without any bindings. The active profile is not changed.
+    pub fn create_profile(&mut self, name: &String) -> Result<()> {
+        if name == &self.active_profile || self.profiles.contains_key(name) {
+            return Err(Error::ProfileAlreadyExists(name.clone()));
+        }
+
+        self.profiles.insert(name.clone(), Bindings::default());
+
+        Ok(())
+    }
+
+    /// Removes an inactive profile along with all of its bindings.
+    pub fn remove_profile(&mut self, name: &String) -> Result<()> {
+        if name == &self.active_profile {
+            return Err(Error::CannotRemoveActiveProfile(name.clone()));
+        }
+
+        match self.profiles.remove(name) {
+            Some(_) => Ok(()),
+            None => Err(Error::ProfileDoesNotExist(name.clone())),
+        }
+    }
+
+    /// Stashes the bindings of the active profile and replaces them with the bindings of the profile `name`.
+    ///
+    /// Keys bound in both profiles stay hooked; only the keys that differ are hooked or unhooked.
+    pub fn set_active_profile(&mut self, name: &String) -> Result<()> {
+        if name == &self.active_profile {
+            return Ok(());
+        }
+
+        let next = match self.profiles.remove(name) {
+            Some(b) => b,
+            None => return Err(Error::ProfileDoesNotExist(name.clone())),
+        };
+        let current = self.export_bindings();
+
+        match self.import_bindings(&next) {
+            Ok(_) => {
+                let previous_name = std::mem::replace(&mut self.active_profile, name.clone());
+                self.profiles.insert(previous_name, current);
+                Ok(())
+            }
+            Err(e) => {
+                self.profiles.insert(name.clone(), next);
+                Err(e)
+            }
+        }
+    }
+
+    /// Removes every action, ordered sequence and gesture and returns them with their state. Does not unhook
+    /// any keys.
+    fn take_bindings(&mut self) -> BindingTables {
+        BindingTables {
+            actions: mem::take(&mut self.actions),
+            reverse_lookup: mem::take(&mut self.reverse_lookup),
+            sequences: mem::take(&mut self.sequences),
+            sequence_lookup: mem::take(&mut self.sequence_lookup),
+            gestures: mem::take(&mut self.gestures),
+            gesture_lookup: mem::take(&mut self.gesture_lookup),
+        }
+    }
+
+    /// Replaces every action, ordered sequence and gesture with ones returned by `take_bindings`. Does not hook
+    /// any keys.
+    fn put_bindings(&mut self, tables: BindingTables) {
+        self.actions = tables.actions;
+        self.reverse_lookup = tables.reverse_lookup;
+        self.sequences = tables.sequences;
+        self.sequence_lookup = tables.sequence_lookup;
+        self.gestures = tables.gestures;
+        self.gesture_lookup = tables.gesture_lookup;
+    }
+
+    /// Adds every entry in `bindings` on top of the existing actions, in the order the key sets were
+    /// registered in. Does not hook any keys.
+    fn add_bindings(&mut self, bindings: &Bindings) -> Result<()> {
+        let mut entries = bindings
+            .actions
+            .iter()
+            .flat_map(|(action_name, list)| list.iter().map(move |binding| (action_name, binding)))
+            .collect::<Vec<(&String, &Binding)>>();
+        // Stable, so entries without an order keep the order of the document
+        entries.sort_by_key(|(_, binding)| (binding.order.is_none(), binding.order));
+
+        for (action_name, binding) in entries {
+            if let Some(gesture) = binding.gesture {
+                self.add_gesture_mapping(action_name, &binding.keys, gesture)?;
+                if binding.payload.is_some() {
+                    self.set_gesture_payload(
+                        action_name,
+                        &binding.keys,
+                        gesture,
+                        binding.payload.clone(),
+                    )?;
+                }
+            } else if binding.steps.is_empty() {
+                self.add_action_mapping(action_name, &binding.keys)?;
+                if binding.priority != 0 {
+                    self.set_binding_priority(&binding.keys, binding.priority)?;
+                }
+                if binding.min_elapsed_time.is_some() {
+                    self.set_binding_min_elapsed_time(&binding.keys, binding.min_elapsed_time)?;
+                }
+                if binding.repeat.is_some() {
+                    self.set_binding_repeat(&binding.keys, binding.repeat)?;
+                }
+                if binding.payload.is_some() {
+                    self.set_binding_payload(action_name, &binding.keys, binding.payload.clone())?;
+                }
+            } else {
+                self.add_sequence_mapping(action_name, &binding.steps)?;
+                if binding.payload.is_some() {
+                    self.set_sequence_payload(
+                        action_name,
+                        &binding.steps,
+                        binding.payload.clone(),
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Hooks every key that a key sequence or ordered sequence depends on and unhooks every key that is no
+    /// longer needed. Keys that are already hooked are left alone.
+    ///
+    /// In capture-all mode, every other key is hooked as well. Failing to hook one of those is not an error.
+    fn sync_hooks(&mut self) -> Result<()> {
+        let mapped = self
+            .reverse_lookup
+            .keys()
+            .chain(self.sequence_lookup.keys())
+            .chain(self.gesture_lookup.keys())
+            .copied()
+            .chain(self.layer_keys())
+            .collect::<HashSet<KeyCode>>();
+
+        let mut needed = mapped.clone();
+        if self.capture_all {
+            needed.extend(
+                ALL_KEY_CODES
+                    .iter()
+                    .filter(|k| !self.unavailable_keys.contains(k)),
+            );
+        }
+
+        let unneeded = self
+            .hooked_keys
+            .difference(&needed)
+            .copied()
+            .collect::<Vec<KeyCode>>();
+        for key in unneeded {
+            self.backend.unregister(key)?;
+            self.hooked_keys.remove(&key);
+            self.held_keys.remove(&key);
+        }
+
+        let missing = needed
+            .difference(&self.hooked_keys)
+            .copied()
+            .collect::<Vec<KeyCode>>();
+        for key in missing {
+            match self.backend.register(key, self.callback_sender.clone()) {
+                Ok(_) => {}
+                Err(_) if !mapped.contains(&key) => {
+                    self.unavailable_keys.insert(key);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            }
+            self.hooked_keys.insert(key);
+        }
+
+        Ok(())
+    }
+
+    /// Checks if any actions have been triggered. Needs to be polled at regular intervals
+    /// or else the receivers might grow infinitely large or the senders might block infinitely.
+    ///
+    /// Every pending key event is processed, up to the max events per poll if one is set. Events over
+    /// the limit are left for the next call. Returns the number of key events processed.
+    pub fn poll(&mut self) -> usize {
+        let mut processed = 0;
+        while self.max_events_per_poll.is_none_or(|max| processed < max) {
+            match self.callback_receiver.try_recv() {
+                Ok(event) => self.process_key_event(event),
+                Err(_) => break,
+            }
+            processed += 1;
+        }
+
+        // Key events still waiting to be processed may prevent a gesture from firing or complete a sequence
+        if self.callback_receiver.is_empty() {
+            let now = Instant::now();
+            self.expire_sequences(now);
+            self.expire_gestures(now);
+            self.expire_repeats(now);
+            self.expire_capture(now);
+            self.expire_layers(now);
+        }
+
+        processed
+    }
+
+    /// Moves the listener onto its own worker thread, which blocks until key events arrive instead of
+    /// needing to be polled. See `ListenerThread`.
+    pub fn spawn(self) -> ListenerThread {
+        ListenerThread::spawn(self)
+    }
+
+    /// Returns the earliest time at which `poll` needs to be called even if no key events arrive,
+    /// e.g. to expire a partially completed sequence or fire a long press. `None` if nothing is pending.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.sequences
+            .values()
+            .filter_map(|sm| sm.deadline(&self.sequence_timeout))
+            .chain(self.gestures.values().filter_map(|gm| gm.deadline()))
+            .chain(self.actions.values().filter_map(|am| am.next_repeat))
+            .chain(
+                self.capture
+                    .as_ref()
+                    .and_then(|c| c.deadline(&self.sequence_timeout)),
+            )
+            .chain(
+                self.layer_stack
+                    .iter()
+                    .filter_map(|a| self.layer_deadline(a)),
+            )
+            .min()
+    }
+
+    /// Expires the sequences that timed out before the key event, then handles it. Only the time between key
+    /// events decides whether a queued step was too late.
+    pub(crate) fn process_key_event(&mut self, event: KeyEvent) {
+        self.expire_sequences(event.time);
+        self.handle_key_event(event);
+    }
+
+    fn handle_key_event(&mut self, event: KeyEvent) {
+        if let Some(sender) = self.key_sender.as_ref() {
+            match sender.send(event) {
+                Ok(_) => {}
+                Err(e) => eprintln!("{e}"),
+            }
+        }
+
+        if let Some(capture) = self.capture.as_mut() {
+            let is_finished = match event.state {
+                KeyState::Pressed => {
+                    capture.press_key(event.key, event.time);
+                    false
+                }
+                KeyState::Released => capture.release_key(event.key, event.time),
+            };
+            if is_finished {
+                self.finish_capture();
+            }
+            return;
+        }
+
+        match event.state {
+            KeyState::Pressed => self.handle_press(event.key, event.time),
+            KeyState::Released => self.handle_release(event.key, event.time),
+        }
+    }
+
+    /// Marks the key as held and emits the actions for every key sequence that is now considered pressed.
+    fn handle_press(&mut self, key: KeyCode, time: Instant) {
+        if !self.capture_all
+            && !self.reverse_lookup.contains_key(&key)
+            && !self.sequence_lookup.contains_key(&key)
+            && !self.gesture_lookup.contains_key(&key)
+            && !self.is_layer_key(&key)
+        {
+            return;
+        }
+
+        // Auto-repeat from the OS sends more presses without releases in between. Without releases, every
+        // press is treated as a new press and no key is ever considered held.
+        let is_os_repeat = self.backend.reports_releases() && !self.held_keys.insert(key);
+
+        if self.press_layer_key(key, time, is_os_repeat) {
+            return;
+        }
+        self.press_leaders(key, time, is_os_repeat);
+
+        self.advance_sequences(key, time);
+
+        // Key sets missing from the tables are skipped below, see `validate`
+        if let Some(v) = self.gesture_lookup.get(&key) {
+            for key_set in v.iter() {
+                match self.gestures.get_mut(key_set) {
+                    Some(gm) => {
+                        let fired = gm.press_key(&self.held_keys, time);
+                        emit_gestures(&mut self.emitter, gm, fired);
+                    }
+                    None => continue,
+                }
+            }
+        }
+
+        let vec = match self.reverse_lookup.get(&key) {
+            Some(v) => v,
+            None => {
+                return;
+            }
+        };
+
+        let mut completed = vec![];
+        for key_set in vec.iter() {
+            match self.actions.get_mut(key_set) {
+                Some(am) => {
+                    if am.is_active && (self.edge_triggered || is_os_repeat && am.repeat.is_some())
+                    {
+                        continue;
+                    }
+                    if am.press(
+                        &key,
+                        time,
+                        self.chord_mode,
+                        &self.min_elapsed_time,
+                        &self.held_keys,
+                    ) {
+                        completed.push(key_set.clone());
+                    }
+                }
+                None => continue,
+            }
+        }
+
+        for key_set in self.resolve(&self.actions, completed) {
+            match self.actions.get_mut(&key_set) {
+                Some(am) => {
+                    am.is_active = true;
+                    am.next_repeat = am.repeat.map(|(delay, _)| time + delay);
+                    self.emitter.emit_with_payloads(
+                        &am.actions,
+                        &am.payloads,
+                        &am.sorted_keys(),
+                        time,
+                        ActionEventKind::Pressed,
+                    );
+                }
+                None => continue,
+            }
+        }
+    }
+
+    /// Applies the resolution policy to the key sequences completed by a single key press and returns the
+    /// ones that should fire.
+    fn resolve(
+        &self,
+        actions: &HashMap<KeySet, ActionMapping>,
+        completed: Vec<KeySet>,
+    ) -> Vec<KeySet> {
+        if completed.len() < 2 {
+            return completed;
+        }
+
+        let mappings = completed
+            .iter()
+            .filter_map(|key_set| actions.get(key_set).map(|am| (key_set, am)))
+            .collect::<Vec<(&KeySet, &ActionMapping)>>();
+
+        match self.resolution_policy {
+            ResolutionPolicy::FireAll => completed,
+            ResolutionPolicy::LongestMatch => mappings
+                .iter()
+                .filter(|(_, am)| {
+                    !mappings
+                        .iter()
+                        .any(|(_, other)| am.is_strict_subset_of(other))
+                })
+                .map(|(key_set, _)| (*key_set).clone())
+                .collect(),
+            ResolutionPolicy::FirstRegistered => mappings
+                .iter()
+                .min_by_key(|(_, am)| am.registration)
+                .map(|(key_set, _)| (*key_set).clone())
+                .into_iter()
+                .collect(),
+            ResolutionPolicy::Priority => {
+                let max = mappings.iter().map(|(_, am)| am.priority).max();
+                mappings
+                    .iter()
+                    .filter(|(_, am)| Some(am.priority) == max)
+                    .map(|(key_set, _)| (*key_set).clone())
+                    .collect()
+            }
+        }
+    }
+
+    /// Marks the key as released and emits a release for every active key sequence containing the key.
+    fn handle_release(&mut self, key: KeyCode, time: Instant) {
+        self.held_keys.remove(&key);
+
+        for sm in self.sequences.values_mut() {
+            sm.release_key(&key);
+        }
+
+        self.release_layer_key(key, time);
+
+        if let Some(v) = self.gesture_lookup.get(&key) {
+            for key_set in v.iter() {
+                match self.gestures.get_mut(key_set) {
+                    Some(gm) => {
+                        let fired = gm.release_key(time);
+                        emit_gestures(&mut self.emitter, gm, fired);
+                    }
+                    None => continue,
+                }
+            }
+        }
+
+        let vec = match self.reverse_lookup.get(&key) {
+            Some(v) => v,
+            None => {
+                return;
+            }
+        };
+
+        for key_set in vec.iter() {
+            match self.actions.get_mut(key_set) {
+                Some(am) => {
+                    // The other side of a modifier may still be held
+                    if !am.is_active || am.is_held(&self.held_keys) {
+                        continue;
+                    }
+                    am.is_active = false;
+                    am.next_repeat = None;
+                    self.emitter.emit_with_payloads(
+                        &am.actions,
+                        &am.payloads,
+                        &am.sorted_keys(),
+                        time,
+                        ActionEventKind::Released,
+                    );
+                }
+                None => continue,
+            }
+        }
+    }
+
+    /// Feeds a key press into every ordered sequence, emitting progress, resets and completed actions.
+    fn advance_sequences(&mut self, key: KeyCode, time: Instant) {
+        for sm in self.sequences.values_mut() {
+            let progress = sm.press_key(
+                &key,
+                time,
+                self.chord_mode,
+                &self.min_elapsed_time,
+                &self.held_keys,
+            );
+
+            let mut kinds = vec![];
+            match progress {
+                SequenceProgress::Unchanged => {}
+                SequenceProgress::Advanced(step) => {
+                    kinds.push(ActionEventKind::SequenceAdvanced(step));
+                }
+                SequenceProgress::Completed => kinds.push(ActionEventKind::Pressed),
+                SequenceProgress::Reset => {
+                    kinds.push(ActionEventKind::SequenceReset);
+                    // The wrong key may have started the sequence over
+                    if sm.progress() > 0 {
+                        kinds.push(ActionEventKind::SequenceAdvanced(sm.progress()));
+                    }
+                }
+            }
+
+            for kind in kinds {
+                self.emitter.emit_with_payloads(
+                    &sm.actions,
+                    &sm.payloads,
+                    &sm.unique_keys(),
+                    time,
+                    kind,
+                );
+            }
+        }
+    }
+
+    /// Resets every ordered sequence whose next step was not pressed within the sequence timeout.
+    fn expire_sequences(&mut self, now: Instant) {
+        for sm in self.sequences.values_mut() {
+            if !sm.is_expired(&self.sequence_timeout, now) {
+                continue;
+            }
+
+            sm.reset();
+            self.emitter.emit_with_payloads(
+                &sm.actions,
+                &sm.payloads,
+                &sm.unique_keys(),
+                now,
+                ActionEventKind::SequenceReset,
+            );
+        }
+    }
+
+    /// Fires every gesture that is due by `now`, i.e. long presses and taps that can no longer become double taps.
+    fn expire_gestures(&mut self, now: Instant) {
+        for gm in self.gestures.values_mut() {
+            let fired = gm.expire(now);
+            emit_gestures(&mut self.emitter, gm, fired);
+        }
+    }
+
+    /// Emits a repeat for every active key sequence whose next repeat is due by `now`. Repeats that were
+    /// missed because `poll` was not called in time are skipped rather than emitted all at once.
+    fn expire_repeats(&mut self, now: Instant) {
+        for am in self.actions.values_mut() {
+            let (next_repeat, interval) = match (am.next_repeat, am.repeat) {
+                (Some(next_repeat), Some((_, interval))) => (next_repeat, interval),
+                _ => continue,
+            };
+            if next_repeat > now {
+                continue;
+            }
+
+            self.emitter.emit_with_payloads(
+                &am.actions,
+                &am.payloads,
+                &am.sorted_keys(),
+                next_repeat,
+                ActionEventKind::Repeat,
+            );
+            am.next_repeat = Some((next_repeat + interval).max(now + interval));
+        }
+    }
+
+    /// Returns the minimum elapsed time as an `f32` in seconds.
+    pub fn get_min_elapsed_time(&self) -> f32 {
+        self.min_elapsed_time.as_secs_f32()
+    }
+
+    /// Converts an `f32` into a `Duration`. Treats the `f32` as seconds.
+    pub fn set_min_elapsed_time(&mut self, min_elapsed_time: f32) -> Result<()> {
+        self.min_elapsed_time = secs_to_duration(min_elapsed_time, "minimum elapsed time")?;
+
+        Ok(())
+    }
+
+    /// Returns the minimum elapsed time of a registered key sequence as an `f32` in seconds, or `None` if
+    /// it uses the listener's minimum elapsed time.
+    pub fn get_binding_min_elapsed_time(&self, keys: &[String]) -> Result<Option<f32>> {
+        let (key_codes, key_set) = string_slice_to_vec_and_key_set(keys)?;
+
+        match self.actions.get(&key_set) {
+            Some(am) => Ok(am.min_elapsed_time.map(|d| d.as_secs_f32())),
+            None => Err(Error::BindingDoesNotExist {
+                keys: keys_to_string(&key_codes),
+                map_type: MapType::Actions,
+            }),
+        }
+    }
+
+    /// Overrides the minimum elapsed time of a registered key sequence. Treats the `f32` as seconds.
+    /// Passing `None` makes the key sequence use the listener's minimum elapsed time again.
+    pub fn set_binding_min_elapsed_time(
+        &mut self,
+        keys: &[String],
+        min_elapsed_time: Option<f32>,
+    ) -> Result<()> {
+        let (key_codes, key_set) = string_slice_to_vec_and_key_set(keys)?;
+        let min_elapsed_time = match min_elapsed_time {
+            Some(secs) => Some(secs_to_duration(secs, "minimum elapsed time")?),
+            None => None,
+        };
+
+        match self.actions.get_mut(&key_set) {
+            Some(am) => {
+                am.min_elapsed_time = min_elapsed_time;
+                Ok(())
+            }
+            None => Err(Error::BindingDoesNotExist {
+                keys: keys_to_string(&key_codes),
+                map_type: MapType::Actions,
+            }),
+        }
+    }
+
+    /// Returns the auto-repeat of a registered key sequence, or `None` if it does not repeat.
+    pub fn get_binding_repeat(&self, keys: &[String]) -> Result<Option<AutoRepeat>> {
+        let (key_codes, key_set) = string_slice_to_vec_and_key_set(keys)?;
+
+        match self.actions.get(&key_set) {
+            Some(am) => Ok(am.repeat.map(|(delay, interval)| AutoRepeat {
+                delay: delay.as_secs_f32(),
+                interval: interval.as_secs_f32(),
+            })),
+            None => Err(Error::BindingDoesNotExist {
+                keys: keys_to_string(&key_codes),
+                map_type: MapType::Actions,
+            }),
+        }
+    }
+
+    /// Makes a registered key sequence emit `ActionEventKind::Repeat` while it is held. Presses sent by the
+    /// OS's own key repeat are ignored for the key sequence while it repeats. Passing `None` stops repeating.
+    ///
+    /// Refused if the `Backend` does not report key releases, since the key sequence would never stop repeating.
+    pub fn set_binding_repeat(
+        &mut self,
+        keys: &[String],
+        repeat: Option<AutoRepeat>,
+    ) -> Result<()> {
+        let (key_codes, key_set) = string_slice_to_vec_and_key_set(keys)?;
+
+        if repeat.is_some() && !self.backend.reports_releases() {
+            return Err(Error::ReleasesNotReported("auto-repeat"));
+        }
+        let repeat = match repeat {
+            Some(r) => Some(r.to_durations()?),
+            None => None,
+        };
+
+        match self.actions.get_mut(&key_set) {
+            Some(am) => {
+                am.repeat = repeat;
+                am.next_repeat = None;
+                Ok(())
+            }
+            None => Err(Error::BindingDoesNotExist {
+                keys: keys_to_string(&key_codes),
+                map_type: MapType::Actions,
+            }),
+        }
+    }
+
+    /// Returns the payload of an action bound to a registered key sequence, or `None` if it has none.
+    pub fn get_binding_payload(
+        &self,
+        action_name: &String,
+        keys: &[String],
+    ) -> Result<Option<Payload>> {
+        let (key_codes, key_set) = string_slice_to_vec_and_key_set(keys)?;
+
+        match self.actions.get(&key_set) {
+            Some(am) if am.actions.contains(action_name) => {
+                Ok(am.payloads.get(action_name).cloned())
+            }
+            _ => Err(Error::ActionDoesNotExist {
+                action: action_name.clone(),
+                keys: keys_to_string(&key_codes),
+                map_type: MapType::Actions,
+            }),
+        }
+    }
+
+    /// Attaches a payload to an action bound to a registered key sequence. The payload is delivered with every
+    /// event the key sequence emits for the action, so the same action can be bound to several key sequences
+    /// with different parameters. Passing `None` removes the payload.
+    pub fn set_binding_payload(
+        &mut self,
+        action_name: &String,
+        keys: &[String],
+        payload: Option<Payload>,
+    ) -> Result<()> {
+        let (key_codes, key_set) = string_slice_to_vec_and_key_set(keys)?;
+
+        match self.actions.get_mut(&key_set) {
+            Some(am) if am.actions.contains(action_name) => {
+                match payload {
+                    Some(payload) => am.payloads.insert(action_name.clone(), payload),
+                    None => am.payloads.remove(action_name),
+                };
+                Ok(())
+            }
+            _ => Err(Error::ActionDoesNotExist {
+                action: action_name.clone(),
+                keys: keys_to_string(&key_codes),
+                map_type: MapType::Actions,
+            }),
+        }
+    }
+
+    /// Returns the payload of an action bound to a registered ordered sequence, or `None` if it has none.
+    pub fn get_sequence_payload(
+        &self,
+        action_name: &String,
+        steps: &[Vec<String>],
+    ) -> Result<Option<Payload>> {
+        let (key_steps, key_sets) = string_steps_to_vec_and_key_sets(steps)?;
+
+        match self.sequences.get(&key_sets) {
+            Some(sm) if sm.actions.contains(action_name) => {
+                Ok(sm.payloads.get(action_name).cloned())
+            }
+            _ => Err(Error::ActionDoesNotExist {
+                action: action_name.clone(),
+                keys: steps_to_string(&key_steps),
+                map_type: MapType::Sequences,
+            }),
+        }
+    }
+
+    /// Attaches a payload to an action bound to a registered ordered sequence, see `set_binding_payload`.
+    pub fn set_sequence_payload(
+        &mut self,
+        action_name: &String,
+        steps: &[Vec<String>],
+        payload: Option<Payload>,
+    ) -> Result<()> {
+        let (key_steps, key_sets) = string_steps_to_vec_and_key_sets(steps)?;
+
+        match self.sequences.get_mut(&key_sets) {
+            Some(sm) if sm.actions.contains(action_name) => {
+                match payload {
+                    Some(payload) => sm.payloads.insert(action_name.clone(), payload),
+                    None => sm.payloads.remove(action_name),
+                };
+                Ok(())
+            }
+            _ => Err(Error::ActionDoesNotExist {
+                action: action_name.clone(),
+                keys: steps_to_string(&key_steps),
+                map_type: MapType::Sequences,
+            }),
+        }
+    }
+
+    /// Returns the payload of an action bound to a registered gesture, or `None` if it has none. Only the kind
+    /// of the gesture needs to match, not its time.
+    pub fn get_gesture_payload(
+        &self,
+        action_name: &String,
+        keys: &[String],
+        gesture: Gesture,
+    ) -> Result<Option<Payload>> {
+        let (key_codes, key_set) = string_slice_to_vec_and_key_set(keys)?;
+
+        let entry = self.gestures.get(&key_set).and_then(|gm| {
+            gm.find_entry(gesture, action_name)
+                .map(|index| &gm.entries[index])
+        });
+
+        match entry {
+            Some(entry) => Ok(entry.payloads.get(action_name).cloned()),
+            None => Err(Error::ActionDoesNotExist {
+                action: action_name.clone(),
+                keys: format!("{} ({})", keys_to_string(&key_codes), gesture.as_str()),
+                map_type: MapType::Gestures,
+            }),
+        }
+    }
+
+    /// Attaches a payload to an action bound to a registered gesture, see `set_binding_payload`. Only the kind
+    /// of the gesture needs to match, not its time.
+    pub fn set_gesture_payload(
+        &mut self,
+        action_name: &String,
+        keys: &[String],
+        gesture: Gesture,
+        payload: Option<Payload>,
+    ) -> Result<()> {
+        let (key_codes, key_set) = string_slice_to_vec_and_key_set(keys)?;
+
+        let entry = self.gestures.get_mut(&key_set).and_then(|gm| {
+            gm.find_entry(gesture, action_name)
+                .map(|index| &mut gm.entries[index])
+        });
+
+        match entry {
+            Some(entry) => {
+                match payload {
+                    Some(payload) => entry.payloads.insert(action_name.clone(), payload),
+                    None => entry.payloads.remove(action_name),
+                };
+                Ok(())
+            }
+            None => Err(Error::ActionDoesNotExist {
+                action: action_name.clone(),
+                keys: format!("{} ({})", keys_to_string(&key_codes), gesture.as_str()),
+                map_type: MapType::Gestures,
+            }),
+        }
+    }
+
+    /// Returns the payload of an action registered with `register_layer_action`, or `None` if it has none.
+    pub fn get_layer_action_payload(
+        &self,
+        layer_name: &String,
+        action_name: &String,
+        keys: &[String],
+    ) -> Result<Option<Payload>> {
+        let (key_codes, key_set) = string_slice_to_vec_and_key_set(keys)?;
+
+        let layer = match self.layers.get(layer_name) {
+            Some(layer) => layer,
+            None => return Err(Error::LayerDoesNotExist(layer_name.clone())),
+        };
+
+        match layer.actions.get(&key_set) {
+            Some(am) if am.actions.contains(action_name) => {
+                Ok(am.payloads.get(action_name).cloned())
+            }
+            _ => Err(Error::ActionDoesNotExist {
+                action: action_name.clone(),
+                keys: keys_to_string(&key_codes),
+                map_type: MapType::Layers,
+            }),
+        }
+    }
+
+    /// Attaches a payload to an action registered with `register_layer_action`, see `set_binding_payload`.
+    pub fn set_layer_action_payload(
+        &mut self,
+        layer_name: &String,
+        action_name: &String,
+        keys: &[String],
+        payload: Option<Payload>,
+    ) -> Result<()> {
+        let (key_codes, key_set) = string_slice_to_vec_and_key_set(keys)?;
+
+        let layer = match self.layers.get_mut(layer_name) {
+            Some(layer) => layer,
+            None => return Err(Error::LayerDoesNotExist(layer_name.clone())),
+        };
+
+        match layer.actions.get_mut(&key_set) {
+            Some(am) if am.actions.contains(action_name) => {
+                match payload {
+                    Some(payload) => am.payloads.insert(action_name.clone(), payload),
+                    None => am.payloads.remove(action_name),
+                };
+                Ok(())
+            }
+            _ => Err(Error::ActionDoesNotExist {
+                action: action_name.clone(),
+                keys: keys_to_string(&key_codes),
+                map_type: MapType::Layers,
+            }),
+        }
+    }
+
+    /// Returns how key sequences completed by the same key press are resolved.
+    pub fn get_resolution_policy(&self) -> ResolutionPolicy {
+        self.resolution_policy
+    }
+
+    /// Changes how key sequences completed by the same key press are resolved.
+    pub fn set_resolution_policy(&mut self, resolution_policy: ResolutionPolicy) {
+        self.resolution_policy = resolution_policy;
+    }
+
+    /// Returns the priority of a registered key sequence. Defaults to 0.
+    pub fn get_binding_priority(&self, keys: &[String]) -> Result<i32> {
+        let (key_codes, key_set) = string_slice_to_vec_and_key_set(keys)?;
+
+        match self.actions.get(&key_set) {
+            Some(am) => Ok(am.priority),
+            None => Err(Error::BindingDoesNotExist {
+                keys: keys_to_string(&key_codes),
+                map_type: MapType::Actions,
+            }),
+        }
+    }
+
+    /// Sets the priority of a registered key sequence, used with `ResolutionPolicy::Priority`. The priority
+    /// applies to every action bound to the key sequence.
+    pub fn set_binding_priority(&mut self, keys: &[String], priority: i32) -> Result<()> {
+        let (key_codes, key_set) = string_slice_to_vec_and_key_set(keys)?;
+
+        match self.actions.get_mut(&key_set) {
+            Some(am) => {
+                am.priority = priority;
+                Ok(())
+            }
+            None => Err(Error::BindingDoesNotExist {
+                keys: keys_to_string(&key_codes),
+                map_type: MapType::Actions,
+            }),
+        }
+    }
+
+    /// Returns the maximum number of key events processed by a single `poll`. `None` means there is no limit.
+    pub fn get_max_events_per_poll(&self) -> Option<usize> {
+        self.max_events_per_poll
+    }
+
+    /// Limits the number of key events processed by a single `poll`. `None` removes the limit.
+    pub fn set_max_events_per_poll(&mut self, max_events_per_poll: Option<usize>) {
+        self.max_events_per_poll = max_events_per_poll;
+    }
+
+    /// Returns the maximum time allowed between steps of an ordered sequence as an `f32` in seconds.
+    pub fn get_sequence_timeout(&self) -> f32 {
+        self.sequence_timeout.as_secs_f32()
+    }
+
+    /// Converts an `f32` into a `Duration`. Treats the `f32` as seconds.
+    pub fn set_sequence_timeout(&mut self, sequence_timeout: f32) -> Result<()> {
+        self.sequence_timeout = secs_to_duration(sequence_timeout, "sequence timeout")?;
+
+        Ok(())
+    }
+
+    /// Returns how key sequences are currently matched.
+    pub fn get_chord_mode(&self) -> ChordMode {
+        self.chord_mode
+    }
+
+    /// Changes how key sequences are matched. Takes effect on the next key press.
+    ///
+    /// `ChordMode::Held` is refused if the `Backend` does not report key releases, since every key would
+    /// appear to be held forever after its first press.
+    pub fn set_chord_mode(&mut self, chord_mode: ChordMode) -> Result<()> {
+        if chord_mode == ChordMode::Held && !self.backend.reports_releases() {
+            return Err(Error::ReleasesNotReported("held chord mode"));
+        }
+
+        self.chord_mode = chord_mode;
+
+        Ok(())
+    }
+
+    /// Starts recording the next chord pressed on any key, e.g. to let the user bind a new shortcut while
+    /// another application is focused. Every key is hooked for the duration of the session and no actions
+    /// are emitted.
+    ///
+    /// The session finishes once every pressed key has been released, or once no key event has arrived for
+    /// the sequence timeout if the `Backend` does not report releases. The chord can then be retrieved with
+    /// `take_capture`. Starting a new session discards any running session.
+    pub fn start_capture(&mut self) -> Result<()> {
+        let restore_capture_all = match self.capture.take() {
+            Some(capture) => capture.restore_capture_all,
+            None => self.capture_all,
+        };
+        self.captured = None;
+
+        self.set_capture_all(true)?;
+        self.capture = Some(CaptureSession::new(restore_capture_all));
+
+        Ok(())
+    }
+
+    /// Stops the running capture session without recording anything.
+    pub fn cancel_capture(&mut self) -> Result<()> {
+        match self.capture.take() {
+            Some(capture) => self.set_capture_all(capture.restore_capture_all),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns whether a capture session is running.
+    pub fn is_capturing(&self) -> bool {
+        self.capture.is_some()
+    }
+
+    /// Returns the chord recorded by the last capture session, in the order the keys were first pressed,
+    /// if it has not been taken yet. The names can be passed to `register_action` as-is.
+    pub fn take_capture(&mut self) -> Option<Vec<String>> {
+        self.captured
+            .take()
+            .map(|keys| keys.iter().map(key_code_name).collect())
+    }
+
+    /// Ends the running capture session and stores its chord.
+    fn finish_capture(&mut self) {
+        let capture = match self.capture.take() {
+            Some(capture) => capture,
+            None => return,
+        };
+
+        self.captured = Some(capture.keys().to_vec());
+        if let Err(e) = self.set_capture_all(capture.restore_capture_all) {
+            eprintln!("{e}");
+        }
+    }
+
+    /// Finishes the running capture session if no key event has arrived for the sequence timeout.
+    fn expire_capture(&mut self, now: Instant) {
+        let is_expired = match self.capture.as_ref() {
+            Some(capture) => capture
+                .deadline(&self.sequence_timeout)
+                .is_some_and(|deadline| now >= deadline),
+            None => false,
+        };
+
+        if is_expired {
+            self.finish_capture();
+        }
+    }
+
+    /// Returns whether every key is hooked, not just the ones used by bindings.
+    pub fn get_capture_all(&self) -> bool {
+        self.capture_all
+    }
+
+    /// When enabled, hooks every key in `ALL_KEY_CODES` so that presses and releases of any key are reported
+    /// through `set_key_sender` and `is_key_held`, whether or not an action uses it. Keys the backend cannot
+    /// hook are skipped.
+    ///
+    /// Every hooked key is considered when matching ordered sequences, so pressing any key resets them.
+    pub fn set_capture_all(&mut self, capture_all: bool) -> Result<()> {
+        self.capture_all = capture_all;
+        if !capture_all {
+            self.unavailable_keys.clear();
+        }
+
+        self.sync_hooks()
+    }
+
+    /// Publishes every raw key press and release on a hooked key through `key_sender`, before it is matched
+    /// against any action. Passing `None` stops publishing.
+    pub fn set_key_sender(&mut self, key_sender: Option<Sender<KeyEvent>>) {
+        self.key_sender = key_sender;
+    }
+
+    /// Returns whether raw key events are being published. See `set_key_sender`.
+    pub fn has_key_sender(&self) -> bool {
+        self.key_sender.is_some()
+    }
+
+    /// Returns whether key sequences fire only once until one of their keys is released.
+    pub fn get_edge_triggered(&self) -> bool {
+        self.edge_triggered
+    }
+
+    /// When enabled, a key sequence fires once when it is pressed and not again until one of its keys is
+    /// released, no matter how many presses the OS sends while the keys are held.
+    ///
+    /// Enabling it is refused if the `Backend` does not report key releases, since every key sequence would
+    /// fire only once.
+    pub fn set_edge_triggered(&mut self, edge_triggered: bool) -> Result<()> {
+        if edge_triggered && !self.backend.reports_releases() {
+            return Err(Error::ReleasesNotReported("edge-triggered mode"));
+        }
+
+        self.edge_triggered = edge_triggered;
+
+        Ok(())
+    }
+
+    /// Returns the cooldown for an action as an `f32` in seconds, or `None` if it has none.
+    pub fn get_action_cooldown(&self, action_name: &String) -> Option<f32> {
+        self.emitter
+            .cooldowns
+            .get(action_name)
+            .map(|d| d.as_secs_f32())
+    }
+
+    /// Sets the minimum time between two `Pressed` events for an action, no matter which key sequence,
+    /// ordered sequence or gesture triggered it. Presses within the cooldown are dropped, along with their
+    /// repeats and release. Treats the `f32` as seconds. Passing `None` removes the cooldown.
+    ///
+    /// The action does not need to be registered.
+    pub fn set_action_cooldown(
+        &mut self,
+        action_name: &String,
+        cooldown: Option<f32>,
+    ) -> Result<()> {
+        match cooldown {
+            Some(cooldown) => {
+                let cooldown = secs_to_duration(cooldown, "cooldown")?;
+                self.emitter.cooldowns.insert(action_name.clone(), cooldown);
+            }
+            None => {
+                self.emitter.cooldowns.remove(action_name);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether a hooked key is currently held down.
+    ///
+    /// Always `false` if the `Backend` does not report key releases.
+    pub fn is_key_held(&self, key: &KeyCode) -> bool {
+        self.held_keys.contains(key)
+    }
+
+    /// Iterates through all actions and returns a non-repeating `Vec` of all registered actions.
+    ///
+    /// The `Vec` is initially unsorted but is sorted in order to remove duplicates.
+    pub fn get_action_names(&self) -> Vec<String> {
+        let mut r = self
+            .actions
+            .values()
+            .flat_map(|am| am.actions.clone())
+            .chain(self.sequences.values().flat_map(|sm| sm.actions.clone()))
+            .chain(
+                self.gestures
+                    .values()
+                    .flat_map(|gm| gm.entries.iter().flat_map(|e| e.actions.clone())),
+            )
+            .collect::<Vec<String>>();
+
+        r.sort_unstable();
+        r.dedup();
+
+        r
+    }
+
+    /// Iterates through every key used by a binding and returns their names as a `Vec`. Side-agnostic
+    /// modifiers like `Control` are returned as-is, so every name can be passed back to `register_action`.
+    ///
+    /// Names are _not_ sorted.
+    pub fn get_key_names(&self) -> Vec<String> {
+        let mut seen = HashSet::new();
+
+        self.actions
+            .values()
+            .flat_map(|am| am.keys.keys())
+            .chain(self.sequences.values().flat_map(|sm| sm.keys()))
+            .chain(self.gestures.values().flat_map(|gm| gm.keys.iter()))
+            .filter(|k| seen.insert(**k))
+            .map(Key::name)
+            .collect::<Vec<String>>()
+    }
+}
+
+/// Emits the actions of every gesture entry returned by a `GestureMapping`.
+fn emit_gestures(emitter: &mut EventEmitter, gm: &GestureMapping, fired: Vec<(usize, Instant)>) {
+    for (index, time) in fired {
+        if let Some(entry) = gm.entries.get(index) {
+            emitter.emit_with_payloads(
+                &entry.actions,
+                &entry.payloads,
+                &gm.keys,
+                time,
+                ActionEventKind::Pressed,
+            );
+        }
+    }
+}
+
+/// Checks a table of bindings against the lookup used to find them by key, see `HotkeyListener::validate`.
+fn validate_lookup<K: Eq + Hash, M>(
+    mappings: &HashMap<K, M>,
+    lookup: &HashMap<KeyCode, Vec<K>>,
+    key_codes: impl Fn(&M) -> Vec<KeyCode>,
+    errors: &mut Vec<Error>,
+) {
+    for (k, m) in mappings.iter() {
+        for key in key_codes(m).into_iter().collect::<HashSet<KeyCode>>() {
+            if !lookup.get(&key).is_some_and(|v| v.contains(k)) {
+                errors.push(Error::MappedKeyMissingInReverseLookup(key));
+            }
+        }
+    }
+
+    for (key, key_sets) in lookup.iter() {
+        for k in key_sets.iter() {
+            let is_used = match mappings.get(k) {
+                Some(m) => key_codes(m).contains(key),
+                None => false,
+            };
+            if !is_used {
+                errors.push(Error::UnmappedKeyInReverseLookup(*key));
+            }
+        }
+    }
+}
+
+/// Parses key names into the keys of a key sequence and the `KeySet` identifying it. The keys are sorted
+/// by name without duplicates, so the order they are given in does not matter.
+fn string_slice_to_vec_and_key_set(keys: &[String]) -> Result<(Vec<Key>, KeySet)> {
+    let key_set = KeySet::from_names(keys)?;
+
+    Ok((key_set.to_vec(), key_set))
+}
+
+/// Converts each step of an ordered sequence with `string_slice_to_vec_and_key_set`. Only keys within a
+/// step are sorted, so the order of the steps matters.
+fn string_steps_to_vec_and_key_sets(steps: &[Vec<String>]) -> Result<(Vec<Vec<Key>>, Vec<KeySet>)> {
+    if steps.is_empty() || steps.iter().any(|s| s.is_empty()) {
+        return Err(Error::EmptySequence);
+    }
+
+    let mut key_sets = vec![];
+    for step in steps.iter() {
+        key_sets.push(KeySet::from_names(step)?);
+    }
+
+    Ok((key_sets.iter().map(|s| s.to_vec()).collect(), key_sets))
+}
+
+/// The longest time accepted by `secs_to_duration`, one year. Deadlines are computed by adding durations to an
+/// `Instant`, which panics if the result cannot be represented.
+const MAX_DURATION_SECS: f32 = 365.0 * 24.0 * 60.0 * 60.0;
+
+/// Converts a number of seconds passed in by the caller into a `Duration`. Negative, NaN and infinite values,
+/// as well as values over a year, are refused with `Error::InvalidDuration`, naming `what` the value was for.
+pub(crate) fn secs_to_duration(secs: f32, what: &'static str) -> Result<Duration> {
+    if secs > MAX_DURATION_SECS {
+        return Err(Error::InvalidDuration { what, secs });
+    }
+
+    match Duration::try_from_secs_f32(secs) {
+        Ok(d) => Ok(d),
+        Err(_) => Err(Error::InvalidDuration { what, secs }),
+    }
+}
 
-    hasher.finish()
+/// Returns the name of a `KeyCode` that can be parsed back with `KeyCode::from_str`, e.g. `KeyA`.
+///
+/// `KeyCode::as_str` is meant for display and returns names like `A` or `⇧ Left` that cannot always be parsed.
+pub(crate) fn key_code_name(key: &KeyCode) -> String {
+    // The derived Debug impl prints the variant name, which is always accepted by the FromStr impl
+    format!("{key:?}")
 }
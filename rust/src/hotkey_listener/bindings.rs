@@ -0,0 +1,207 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::hotkey_listener::{
+    secs_to_duration, string_steps_to_vec_and_key_sets, AutoRepeat, Error, Gesture, KeySet,
+    Payload, Result,
+};
+
+/// A serializable snapshot of every action registered on a `HotkeyListener`.
+///
+/// In TOML, each action is an array of tables:
+///
+/// ```toml
+/// [[actions.Save]]
+/// keys = ["ControlLeft", "KeyS"]
+///
+/// [[actions.Save]]
+/// steps = [["ControlLeft", "KeyK"], ["ControlLeft", "KeyS"]]
+///
+/// [[actions.SetExpression]]
+/// keys = ["Control", "Digit1"]
+/// payload = { id = "smile" }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Bindings {
+    #[serde(default)]
+    pub actions: BTreeMap<String, Vec<Binding>>,
+}
+
+/// A single way of triggering an action. Exactly one of `keys` or `steps` must be set. A `gesture` requires
+/// `keys`. The options that only apply to `keys` cannot be set with `steps` or a `gesture`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Binding {
+    /// Keys that must be pressed together, see `HotkeyListener::register_action`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<String>,
+    /// Key sequences that must be pressed in order, see `HotkeyListener::register_sequence`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<Vec<String>>,
+    /// See `HotkeyListener::set_binding_priority`. Only applies to `keys` without a `gesture`.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub priority: i32,
+    /// When the key set was registered relative to the others, which decides the winner under
+    /// `ResolutionPolicy::FirstRegistered`. Key sets are registered in this order on import, followed by the
+    /// ones without an order. Only applies to `keys` without a `gesture`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<u64>,
+    /// See `HotkeyListener::set_binding_min_elapsed_time`. Only applies to `keys` without a `gesture`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_elapsed_time: Option<f32>,
+    /// See `HotkeyListener::set_binding_repeat`. Only applies to `keys` without a `gesture`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat: Option<AutoRepeat>,
+    /// See `HotkeyListener::register_gesture`. Only applies to `keys`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gesture: Option<Gesture>,
+    /// See `HotkeyListener::set_binding_payload`, or `HotkeyListener::set_gesture_payload` with a `gesture`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<Payload>,
+}
+
+impl Binding {
+    /// Whether any option that only applies to a key set without a gesture is set.
+    fn has_key_set_options(&self) -> bool {
+        self.priority != 0
+            || self.order.is_some()
+            || self.min_elapsed_time.is_some()
+            || self.repeat.is_some()
+    }
+
+    /// Checks every time in seconds, so that importing the binding cannot fail halfway because of one.
+    fn validate_durations(&self) -> Result<()> {
+        if let Some(secs) = self.min_elapsed_time {
+            secs_to_duration(secs, "minimum elapsed time")?;
+        }
+        if let Some(repeat) = self.repeat {
+            repeat.to_durations()?;
+        }
+        if let Some(gesture) = self.gesture {
+            gesture.validate()?;
+        }
+
+        Ok(())
+    }
+}
+
+fn is_zero(n: &i32) -> bool {
+    *n == 0
+}
+
+/// Why a single entry in a `Bindings` document could not be imported.
+#[derive(Debug)]
+pub struct BindingError {
+    pub action: String,
+    /// Position of the entry in the action's list of bindings.
+    pub index: usize,
+    pub error: Error,
+}
+
+impl fmt::Display for BindingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "binding {} of `{}`: {}",
+            self.index, self.action, self.error
+        )
+    }
+}
+
+impl std::error::Error for BindingError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// What makes two bindings of an action the same, see `Bindings::validate`.
+#[derive(PartialEq, Eq, Hash)]
+enum BindingId {
+    /// A key sequence, with the kind of gesture if any.
+    Keys(KeySet, Option<&'static str>),
+    Steps(Vec<KeySet>),
+}
+
+impl Bindings {
+    /// Parses a TOML bindings document. Entries are _not_ validated.
+    pub fn from_toml(s: &str) -> Result<Self> {
+        match toml::from_str(s) {
+            Ok(b) => Ok(b),
+            Err(e) => Err(Error::BadBindingsDocument(e)),
+        }
+    }
+
+    /// Converts the bindings to a TOML document.
+    pub fn to_toml(&self) -> Result<String> {
+        match toml::to_string_pretty(self) {
+            Ok(s) => Ok(s),
+            Err(e) => Err(Error::CannotSerializeBindings(e)),
+        }
+    }
+
+    /// Checks every entry without registering anything. All problems are returned, not just the first one.
+    pub fn validate(&self) -> std::result::Result<(), Vec<BindingError>> {
+        let mut errors = vec![];
+        let mut seen: HashSet<(&String, BindingId)> = HashSet::new();
+
+        for (action, bindings) in self.actions.iter() {
+            for (index, binding) in bindings.iter().enumerate() {
+                let id = match (binding.keys.is_empty(), binding.steps.is_empty()) {
+                    (false, true)
+                        if binding.gesture.is_none() || !binding.has_key_set_options() =>
+                    {
+                        KeySet::from_names(&binding.keys).map(|key_set| {
+                            BindingId::Keys(key_set, binding.gesture.map(|g| g.as_str()))
+                        })
+                    }
+                    (true, false)
+                        if binding.gesture.is_none() && !binding.has_key_set_options() =>
+                    {
+                        string_steps_to_vec_and_key_sets(&binding.steps)
+                            .map(|(_, key_sets)| BindingId::Steps(key_sets))
+                    }
+                    _ => Err(Error::InvalidBinding),
+                };
+                let id = id.and_then(|id| binding.validate_durations().map(|_| id));
+
+                let error = match id {
+                    Ok(id) => {
+                        if seen.insert((action, id)) {
+                            continue;
+                        }
+                        let keys = if binding.steps.is_empty() {
+                            binding.keys.join("+")
+                        } else {
+                            binding
+                                .steps
+                                .iter()
+                                .map(|step| step.join("+"))
+                                .collect::<Vec<String>>()
+                                .join(", ")
+                        };
+                        Error::ActionAlreadyExists {
+                            action: action.clone(),
+                            keys,
+                        }
+                    }
+                    Err(e) => e,
+                };
+
+                errors.push(BindingError {
+                    action: action.clone(),
+                    index,
+                    error,
+                });
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant},
+};
+
+use livesplit_hotkey::KeyCode;
+
+/// Records the chord pressed while binding a new shortcut, see `HotkeyListener::start_capture`.
+#[derive(Debug)]
+pub(crate) struct CaptureSession {
+    /// Every key pressed during the session, in the order they were first pressed.
+    keys: Vec<KeyCode>,
+    held: HashSet<KeyCode>,
+    /// When the last key event was received. `None` until the first press.
+    last_event: Option<Instant>,
+    /// Whether capture-all mode was enabled before the session started.
+    pub(crate) restore_capture_all: bool,
+}
+
+impl CaptureSession {
+    pub(crate) fn new(restore_capture_all: bool) -> Self {
+        CaptureSession {
+            keys: vec![],
+            held: HashSet::new(),
+            last_event: None,
+            restore_capture_all,
+        }
+    }
+
+    pub(crate) fn keys(&self) -> &[KeyCode] {
+        &self.keys
+    }
+
+    pub(crate) fn press_key(&mut self, key: KeyCode, time: Instant) {
+        if !self.keys.contains(&key) {
+            self.keys.push(key);
+        }
+        self.held.insert(key);
+        self.last_event = Some(time);
+    }
+
+    /// Returns `true` once every pressed key has been released, which finishes the session.
+    pub(crate) fn release_key(&mut self, key: KeyCode, time: Instant) -> bool {
+        self.held.remove(&key);
+        self.last_event = Some(time);
+
+        !self.keys.is_empty() && self.held.is_empty()
+    }
+
+    /// When the session finishes on its own if no further key event arrives. Needed for backends that do
+    /// not report key releases. `None` if no key has been pressed yet.
+    pub(crate) fn deadline(&self, timeout: &Duration) -> Option<Instant> {
+        self.last_event.map(|last| last + *timeout)
+    }
+}
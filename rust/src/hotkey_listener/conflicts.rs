@@ -0,0 +1,100 @@
+use crate::hotkey_listener::{keys_imply, ActionMapping, Key};
+
+/// A key sequence and every action bound to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictingBinding {
+    /// Sorted by name.
+    pub keys: Vec<Key>,
+    /// Sorted.
+    pub actions: Vec<String>,
+}
+
+/// A pair of key sequences, or a single key sequence, that can lead to surprising behavior in `poll`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conflict {
+    /// More than one action is bound to the exact same keys, so they always fire together.
+    Duplicate(ConflictingBinding),
+    /// Every key in `subset` is matched by a key in `superset`, so pressing `superset` also fires `subset`.
+    Overlap {
+        subset: ConflictingBinding,
+        superset: ConflictingBinding,
+    },
+    /// The key sequences have some physical keys in common but neither contains the other.
+    SharedKeys {
+        first: ConflictingBinding,
+        second: ConflictingBinding,
+        /// The keys of `first` that match a key of `second`, sorted by name.
+        shared: Vec<Key>,
+    },
+}
+
+/// Compares every pair of key sequences. Conflicts are sorted by kind and then by key names so that the
+/// report is stable between calls.
+pub(crate) fn find_conflicts<'a, I>(mappings: I) -> Vec<Conflict>
+where
+    I: Iterator<Item = &'a ActionMapping>,
+{
+    let mut bindings = mappings
+        .map(|am| {
+            let mut actions = am.actions.clone();
+            actions.sort_unstable();
+
+            ConflictingBinding {
+                keys: am.sorted_keys(),
+                actions,
+            }
+        })
+        .collect::<Vec<ConflictingBinding>>();
+    bindings.sort_by_cached_key(key_names);
+
+    let mut duplicates = vec![];
+    let mut overlaps = vec![];
+    let mut shared_keys = vec![];
+
+    for (i, first) in bindings.iter().enumerate() {
+        if first.actions.len() > 1 {
+            duplicates.push(Conflict::Duplicate(first.clone()));
+        }
+
+        for second in bindings.iter().skip(i + 1) {
+            let shared = first
+                .keys
+                .iter()
+                .filter(|k| second.keys.iter().any(|other| k.overlaps(other)))
+                .copied()
+                .collect::<Vec<Key>>();
+
+            if shared.is_empty() {
+                continue;
+            }
+
+            if keys_imply(&second.keys, &first.keys) {
+                overlaps.push(Conflict::Overlap {
+                    subset: first.clone(),
+                    superset: second.clone(),
+                });
+            } else if keys_imply(&first.keys, &second.keys) {
+                overlaps.push(Conflict::Overlap {
+                    subset: second.clone(),
+                    superset: first.clone(),
+                });
+            } else {
+                shared_keys.push(Conflict::SharedKeys {
+                    first: first.clone(),
+                    second: second.clone(),
+                    shared,
+                });
+            }
+        }
+    }
+
+    duplicates
+        .into_iter()
+        .chain(overlaps)
+        .chain(shared_keys)
+        .collect()
+}
+
+fn key_names(binding: &ConflictingBinding) -> Vec<String> {
+    binding.keys.iter().map(Key::name).collect()
+}
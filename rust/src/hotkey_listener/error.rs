@@ -0,0 +1,252 @@
+use std::{fmt, path::PathBuf};
+
+use livesplit_hotkey::KeyCode;
+
+use crate::hotkey_listener::{key_code_name, BindingError, Key};
+
+/// Everything that can go wrong while setting up or configuring a `HotkeyListener`.
+///
+/// Key sets are formatted with `keys_to_string`, e.g. `Control+KeyS`, and ordered sequences with
+/// `steps_to_string`, e.g. `Control+KeyK, Control+KeyS`.
+#[derive(Debug)]
+pub enum Error {
+    /// The OS hook could not be created.
+    HookCreate(livesplit_hotkey::Error),
+
+    /// The action is already bound to the key set.
+    ActionAlreadyExists {
+        action: String,
+        keys: String,
+    },
+    /// The action is not bound to the key set.
+    ActionDoesNotExist {
+        action: String,
+        keys: String,
+        map_type: MapType,
+    },
+    /// Nothing is bound to the key set.
+    BindingDoesNotExist {
+        keys: String,
+        map_type: MapType,
+    },
+    /// The backend was asked to unhook a key that is not hooked.
+    KeyNotMapped(KeyCode),
+
+    /// A key is used by a binding but missing from the lookup used to find the binding. Indicates a bug.
+    MappedKeyMissingInReverseLookup(KeyCode),
+    /// The lookup leads from a key to a binding that does not exist or does not use the key. Indicates a bug.
+    UnmappedKeyInReverseLookup(KeyCode),
+
+    /// Not the name of a `Key`.
+    BadKeyCodeName(String),
+    /// Two keys in a key set match the same physical key, e.g. `Control` and `ControlLeft`.
+    OverlappingKeys(String, String),
+    /// A key set, an ordered sequence or one of its steps is empty.
+    EmptySequence,
+    /// A time in seconds is negative, NaN or too large, or a repeat interval is 0.
+    InvalidDuration {
+        what: &'static str,
+        secs: f32,
+    },
+    /// A binding must have either keys or steps, but not both. A gesture requires keys. The priority, order,
+    /// minimum elapsed time and repeat only apply to keys without a gesture.
+    InvalidBinding,
+    InvalidBindings(Vec<BindingError>),
+    BadBindingsDocument(toml::de::Error),
+    CannotSerializeBindings(toml::ser::Error),
+    CannotAccessBindingsFile {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    /// Importing bindings failed with `error`, and the previous bindings could not be hooked again either.
+    RestoreFailed {
+        error: Box<Error>,
+        restore_error: Box<Error>,
+    },
+
+    ProfileAlreadyExists(String),
+    ProfileDoesNotExist(String),
+    CannotRemoveActiveProfile(String),
+    LayerAlreadyExists(String),
+    LayerDoesNotExist(String),
+    /// The feature needs to know when keys are released, but the `Backend` only reports presses.
+    ReleasesNotReported(&'static str),
+    CannotRegisterHotkey(KeyCode, livesplit_hotkey::Error),
+    CannotUnregisterHotkey(KeyCode, livesplit_hotkey::Error),
+}
+
+/// The kind of binding an error refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapType {
+    /// Key sets, see `HotkeyListener::register_action`.
+    Actions,
+    /// Ordered sequences, see `HotkeyListener::register_sequence`.
+    Sequences,
+    /// Gestures, see `HotkeyListener::register_gesture`.
+    Gestures,
+    /// Key sets of a layer, see `HotkeyListener::register_layer_action`.
+    Layers,
+}
+
+impl MapType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MapType::Actions => "key set",
+            MapType::Sequences => "ordered sequence",
+            MapType::Gestures => "gesture",
+            MapType::Layers => "layer key set",
+        }
+    }
+}
+
+impl Error {
+    /// Returns the name of the variant, which stays the same across versions and can be matched on by
+    /// callers that only see the error as text, e.g. GDScript.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::HookCreate(_) => "HookCreate",
+            Error::ActionAlreadyExists { .. } => "ActionAlreadyExists",
+            Error::ActionDoesNotExist { .. } => "ActionDoesNotExist",
+            Error::BindingDoesNotExist { .. } => "BindingDoesNotExist",
+            Error::KeyNotMapped(_) => "KeyNotMapped",
+            Error::MappedKeyMissingInReverseLookup(_) => "MappedKeyMissingInReverseLookup",
+            Error::UnmappedKeyInReverseLookup(_) => "UnmappedKeyInReverseLookup",
+            Error::BadKeyCodeName(_) => "BadKeyCodeName",
+            Error::OverlappingKeys(..) => "OverlappingKeys",
+            Error::EmptySequence => "EmptySequence",
+            Error::InvalidDuration { .. } => "InvalidDuration",
+            Error::InvalidBinding => "InvalidBinding",
+            Error::InvalidBindings(_) => "InvalidBindings",
+            Error::BadBindingsDocument(_) => "BadBindingsDocument",
+            Error::CannotSerializeBindings(_) => "CannotSerializeBindings",
+            Error::CannotAccessBindingsFile { .. } => "CannotAccessBindingsFile",
+            Error::RestoreFailed { .. } => "RestoreFailed",
+            Error::ProfileAlreadyExists(_) => "ProfileAlreadyExists",
+            Error::ProfileDoesNotExist(_) => "ProfileDoesNotExist",
+            Error::CannotRemoveActiveProfile(_) => "CannotRemoveActiveProfile",
+            Error::LayerAlreadyExists(_) => "LayerAlreadyExists",
+            Error::LayerDoesNotExist(_) => "LayerDoesNotExist",
+            Error::ReleasesNotReported(_) => "ReleasesNotReported",
+            Error::CannotRegisterHotkey(..) => "CannotRegisterHotkey",
+            Error::CannotUnregisterHotkey(..) => "CannotUnregisterHotkey",
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::HookCreate(e) => write!(f, "cannot create the OS hook: {e}"),
+            Error::ActionAlreadyExists { action, keys } => {
+                write!(f, "action `{action}` is already bound to `{keys}`")
+            }
+            Error::ActionDoesNotExist {
+                action,
+                keys,
+                map_type,
+            } => write!(
+                f,
+                "action `{action}` is not bound to the {} `{keys}`",
+                map_type.as_str()
+            ),
+            Error::BindingDoesNotExist { keys, map_type } => {
+                write!(f, "nothing is bound to the {} `{keys}`", map_type.as_str())
+            }
+            Error::KeyNotMapped(key) => write!(f, "key `{}` is not hooked", key_code_name(key)),
+            Error::MappedKeyMissingInReverseLookup(key) => write!(
+                f,
+                "key `{}` is bound but missing from the lookup",
+                key_code_name(key)
+            ),
+            Error::UnmappedKeyInReverseLookup(key) => write!(
+                f,
+                "key `{}` is in the lookup but not used by the binding it leads to",
+                key_code_name(key)
+            ),
+            Error::BadKeyCodeName(name) => write!(f, "`{name}` is not a key name"),
+            Error::OverlappingKeys(first, second) => {
+                write!(f, "`{first}` and `{second}` match the same key")
+            }
+            Error::EmptySequence => {
+                write!(f, "a key set or a step of an ordered sequence is empty")
+            }
+            Error::InvalidDuration { what, secs } => {
+                write!(f, "`{secs}` seconds is not a valid {what}")
+            }
+            Error::InvalidBinding => write!(
+                f,
+                "a binding must have either keys or steps, a gesture requires keys, and priority, order, minimum \
+                 elapsed time and repeat only apply to keys without a gesture"
+            ),
+            Error::InvalidBindings(errors) => {
+                write!(f, "{} invalid bindings", errors.len())?;
+                for (i, e) in errors.iter().enumerate() {
+                    write!(f, "{} {e}", if i == 0 { ":" } else { ";" })?;
+                }
+                Ok(())
+            }
+            Error::BadBindingsDocument(e) => write!(f, "cannot parse bindings: {e}"),
+            Error::CannotSerializeBindings(e) => write!(f, "cannot serialize bindings: {e}"),
+            Error::CannotAccessBindingsFile { path, error } => {
+                write!(f, "cannot access `{}`: {error}", path.display())
+            }
+            Error::RestoreFailed {
+                error,
+                restore_error,
+            } => write!(
+                f,
+                "{error}, and restoring the previous bindings failed: {restore_error}"
+            ),
+            Error::ProfileAlreadyExists(name) => write!(f, "profile `{name}` already exists"),
+            Error::ProfileDoesNotExist(name) => write!(f, "profile `{name}` does not exist"),
+            Error::CannotRemoveActiveProfile(name) => {
+                write!(f, "profile `{name}` is active and cannot be removed")
+            }
+            Error::LayerAlreadyExists(name) => write!(f, "layer `{name}` already exists"),
+            Error::LayerDoesNotExist(name) => write!(f, "layer `{name}` does not exist"),
+            Error::ReleasesNotReported(feature) => write!(
+                f,
+                "{feature} requires key releases, which the backend does not report"
+            ),
+            Error::CannotRegisterHotkey(key, e) => {
+                write!(f, "cannot hook key `{}`: {e}", key_code_name(key))
+            }
+            Error::CannotUnregisterHotkey(key, e) => {
+                write!(f, "cannot unhook key `{}`: {e}", key_code_name(key))
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::HookCreate(e) => Some(e),
+            Error::BadBindingsDocument(e) => Some(e),
+            Error::CannotSerializeBindings(e) => Some(e),
+            Error::CannotAccessBindingsFile { error, .. } => Some(error),
+            Error::RestoreFailed { error, .. } => Some(error.as_ref()),
+            Error::CannotRegisterHotkey(_, e) | Error::CannotUnregisterHotkey(_, e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Formats a key set for an error message, e.g. `Control+KeyS`.
+pub(crate) fn keys_to_string(keys: &[Key]) -> String {
+    keys.iter()
+        .map(Key::name)
+        .collect::<Vec<String>>()
+        .join("+")
+}
+
+/// Formats an ordered sequence for an error message, e.g. `Control+KeyK, Control+KeyS`.
+pub(crate) fn steps_to_string(steps: &[Vec<Key>]) -> String {
+    steps
+        .iter()
+        .map(|step| keys_to_string(step))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
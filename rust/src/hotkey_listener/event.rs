@@ -0,0 +1,169 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use crossbeam_channel::Sender;
+
+use crate::hotkey_listener::{Key, Payload};
+
+/// What happened to the key sequence for an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionEventKind {
+    /// The key sequence for the action was pressed.
+    Pressed,
+    /// A key in the previously pressed key sequence for the action was released.
+    Released,
+    /// The key sequence for the action is still being held down.
+    Repeat,
+    /// A step of an ordered sequence for the action was completed. Contains the number of completed steps.
+    SequenceAdvanced(usize),
+    /// An ordered sequence for the action was started over after a wrong key or a timeout.
+    SequenceReset,
+    /// The layer named by the action was entered. The keys are its leader, if it was entered with one.
+    LayerEntered,
+    /// The layer named by the action was exited, either by Escape, a timeout or `HotkeyListener::pop_layer`.
+    LayerExited,
+}
+
+impl ActionEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActionEventKind::Pressed => "Pressed",
+            ActionEventKind::Released => "Released",
+            ActionEventKind::Repeat => "Repeat",
+            ActionEventKind::SequenceAdvanced(_) => "SequenceAdvanced",
+            ActionEventKind::SequenceReset => "SequenceReset",
+            ActionEventKind::LayerEntered => "LayerEntered",
+            ActionEventKind::LayerExited => "LayerExited",
+        }
+    }
+}
+
+/// Sent to the listener's consumer whenever the key sequence for an action changes state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionEvent {
+    pub action: String,
+    /// Every key in the binding that produced the event.
+    pub keys: Vec<Key>,
+    /// When the key event that caused this event was received by the backend.
+    pub timestamp: Instant,
+    pub kind: ActionEventKind,
+    /// The payload attached to the action for the binding that produced the event, if any.
+    pub payload: Option<Payload>,
+    /// Monotonically increasing for every event sent by a single `HotkeyListener`, starting at 0.
+    pub serial: u64,
+}
+
+/// Builds `ActionEvent`s and hands out their serial numbers.
+pub(crate) struct EventEmitter {
+    sender: Sender<ActionEvent>,
+    next_serial: u64,
+
+    /// Minimum time between two `Pressed` events for an action.
+    pub(crate) cooldowns: HashMap<String, Duration>,
+    /// When the last `Pressed` event was sent for every action with a cooldown.
+    last_pressed: HashMap<String, Instant>,
+    /// Actions and keys whose last `Pressed` event was dropped by a cooldown, so that the `Repeat` and
+    /// `Released` events that follow it are dropped as well.
+    suppressed: HashSet<(String, Vec<Key>)>,
+}
+
+impl EventEmitter {
+    pub(crate) fn new(sender: Sender<ActionEvent>) -> Self {
+        EventEmitter {
+            sender,
+            next_serial: 0,
+
+            cooldowns: HashMap::new(),
+            last_pressed: HashMap::new(),
+            suppressed: HashSet::new(),
+        }
+    }
+
+    /// Checks if an action is still cooling down from its last `Pressed` event and, if not, starts a new
+    /// cooldown at `timestamp`.
+    fn is_cooling_down(&mut self, action: &String, timestamp: Instant) -> bool {
+        let cooldown = match self.cooldowns.get(action) {
+            Some(cooldown) => *cooldown,
+            None => return false,
+        };
+
+        if let Some(last) = self.last_pressed.get(action) {
+            if timestamp.saturating_duration_since(*last) < cooldown {
+                return true;
+            }
+        }
+        self.last_pressed.insert(action.clone(), timestamp);
+
+        false
+    }
+
+    /// Checks if an event belongs to a press that was dropped by a cooldown. A `Pressed` event that is sent
+    /// ends the suppression and a `Released` event is the last one suppressed.
+    fn is_suppressed(&mut self, action: &str, keys: &[Key], kind: ActionEventKind) -> bool {
+        if self.suppressed.is_empty() {
+            return false;
+        }
+
+        let id = (action.to_string(), keys.to_vec());
+        match kind {
+            ActionEventKind::Pressed => {
+                self.suppressed.remove(&id);
+                false
+            }
+            ActionEventKind::Repeat => self.suppressed.contains(&id),
+            ActionEventKind::Released => self.suppressed.remove(&id),
+            _ => false,
+        }
+    }
+
+    /// Sends one event per action, all sharing the same keys, timestamp and kind.
+    ///
+    /// `Pressed` events for actions that are cooling down are dropped, along with the `Repeat` and `Released`
+    /// events of the same press.
+    pub(crate) fn emit(
+        &mut self,
+        actions: &[String],
+        keys: &[Key],
+        timestamp: Instant,
+        kind: ActionEventKind,
+    ) {
+        self.emit_with_payloads(actions, &HashMap::new(), keys, timestamp, kind);
+    }
+
+    /// Same as `emit`, but every event carries the action's entry in `payloads`, if any.
+    pub(crate) fn emit_with_payloads(
+        &mut self,
+        actions: &[String],
+        payloads: &HashMap<String, Payload>,
+        keys: &[Key],
+        timestamp: Instant,
+        kind: ActionEventKind,
+    ) {
+        for action in actions.iter() {
+            if kind == ActionEventKind::Pressed && self.is_cooling_down(action, timestamp) {
+                self.suppressed.insert((action.clone(), keys.to_vec()));
+                continue;
+            }
+            if self.is_suppressed(action, keys, kind) {
+                continue;
+            }
+
+            let event = ActionEvent {
+                action: action.clone(),
+                keys: keys.to_vec(),
+                timestamp,
+                kind,
+                payload: payloads.get(action).cloned(),
+                serial: self.next_serial,
+            };
+            self.next_serial += 1;
+
+            match self.sender.send(event) {
+                Ok(_) => {}
+                Err(e) => eprintln!("{e}"),
+            }
+        }
+    }
+}
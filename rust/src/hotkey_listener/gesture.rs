@@ -0,0 +1,316 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use livesplit_hotkey::KeyCode;
+use serde::{Deserialize, Serialize};
+
+use crate::hotkey_listener::{
+    keys_to_string, secs_to_duration, Error, Key, MapType, Payload, Result,
+};
+
+/// How a set of keys must be pressed for a gesture binding to fire. Times are in seconds.
+///
+/// Gestures rely on key releases, so they are refused if the `Backend` does not report them.
+///
+/// In TOML, a gesture is an inline table:
+///
+/// ```toml
+/// [[actions.Recenter]]
+/// keys = ["F1"]
+/// gesture = { kind = "LongPress", time = 0.5 }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum Gesture {
+    /// Pressed and released. If a double tap is registered on the same keys, fires only once the
+    /// double tap can no longer happen. If a long press is registered on the same keys, does not fire
+    /// when the keys were held long enough for the long press.
+    Tap,
+    /// Tapped twice, with the second press no more than `time` after the first release.
+    DoubleTap { time: f32 },
+    /// Held for at least `time`. Fires while the keys are still held.
+    LongPress { time: f32 },
+}
+
+impl Gesture {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Gesture::Tap => "Tap",
+            Gesture::DoubleTap { .. } => "DoubleTap",
+            Gesture::LongPress { .. } => "LongPress",
+        }
+    }
+
+    /// Returns the gesture's time as a `Duration`. `None` for `Tap`, or if the time is invalid, see `validate`.
+    fn time(&self) -> Option<Duration> {
+        match self {
+            Gesture::Tap => None,
+            Gesture::DoubleTap { time } | Gesture::LongPress { time } => {
+                secs_to_duration(*time, "gesture time").ok()
+            }
+        }
+    }
+
+    /// Checks that the gesture's time is a valid number of seconds.
+    pub(crate) fn validate(&self) -> Result<()> {
+        match self {
+            Gesture::Tap => Ok(()),
+            Gesture::DoubleTap { time } | Gesture::LongPress { time } => {
+                secs_to_duration(*time, "gesture time").map(|_| ())
+            }
+        }
+    }
+}
+
+/// Actions bound to a single gesture.
+#[derive(Debug)]
+pub(crate) struct GestureEntry {
+    pub(crate) gesture: Gesture,
+    pub(crate) actions: Vec<String>,
+    /// Payloads of `actions`, see `HotkeyListener::set_gesture_payload`.
+    pub(crate) payloads: HashMap<String, Payload>,
+    /// Whether a long press already fired during the current hold.
+    fired: bool,
+}
+
+/// Stores every gesture bound to a set of keys along with the state needed to tell them apart.
+#[derive(Debug)]
+pub(crate) struct GestureMapping {
+    /// Sorted by name.
+    pub(crate) keys: Vec<Key>,
+    pub(crate) entries: Vec<GestureEntry>,
+
+    /// When every key became held. `None` while any key is released.
+    down_since: Option<Instant>,
+    /// Whether the current hold already fired a long press or double tap, so releasing it is not a tap.
+    consumed: bool,
+    /// When a tap was released that may still turn into a double tap.
+    pending_tap: Option<Instant>,
+}
+
+impl GestureMapping {
+    pub(crate) fn new(keys: Vec<Key>) -> Self {
+        GestureMapping {
+            keys,
+            entries: vec![],
+
+            down_since: None,
+            consumed: false,
+            pending_tap: None,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The longest double tap window, or `None` if no double tap is registered.
+    fn double_tap_window(&self) -> Option<Duration> {
+        self.entries
+            .iter()
+            .filter(|e| matches!(e.gesture, Gesture::DoubleTap { .. }))
+            .filter_map(|e| e.gesture.time())
+            .max()
+    }
+
+    /// Returns the index of every entry for the given kind of gesture, fired at `time`.
+    fn fire(&self, kind: &str, time: Instant) -> Vec<(usize, Instant)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.gesture.as_str() == kind)
+            .map(|(i, _)| (i, time))
+            .collect()
+    }
+
+    /// Fires the pending tap if it can no longer become a double tap by `now`.
+    fn flush_tap(&mut self, now: Instant) -> Vec<(usize, Instant)> {
+        let (released, window) = match (self.pending_tap, self.double_tap_window()) {
+            (Some(released), Some(window)) => (released, window),
+            _ => return vec![],
+        };
+        if now <= released + window {
+            return vec![];
+        }
+
+        self.pending_tap = None;
+
+        self.fire("Tap", released + window)
+    }
+
+    /// Fires every long press whose time has been reached by `now` during the current hold.
+    fn fire_long_presses(&mut self, now: Instant) -> Vec<(usize, Instant)> {
+        let since = match self.down_since {
+            Some(since) => since,
+            None => return vec![],
+        };
+
+        let mut r = vec![];
+        for (i, e) in self.entries.iter_mut().enumerate() {
+            if e.fired || !matches!(e.gesture, Gesture::LongPress { .. }) {
+                continue;
+            }
+            let at = since + e.gesture.time().unwrap_or_default();
+            if now >= at {
+                e.fired = true;
+                r.push((i, at));
+            }
+        }
+        if !r.is_empty() {
+            self.consumed = true;
+        }
+
+        r
+    }
+
+    /// Advances the state machine with a key press. `held` must already contain the pressed key.
+    ///
+    /// Returns the index of every entry that fired along with when it fired.
+    pub(crate) fn press_key(
+        &mut self,
+        held: &HashSet<KeyCode>,
+        time: Instant,
+    ) -> Vec<(usize, Instant)> {
+        let mut r = self.flush_tap(time);
+        if self.down_since.is_some() || !self.keys.iter().all(|k| k.is_held(held)) {
+            return r;
+        }
+
+        self.down_since = Some(time);
+        self.consumed = false;
+        for e in self.entries.iter_mut() {
+            e.fired = false;
+        }
+
+        if let Some(released) = self.pending_tap.take() {
+            let double_taps = self
+                .entries
+                .iter()
+                .enumerate()
+                .filter(|(_, e)| matches!(e.gesture, Gesture::DoubleTap { .. }))
+                .filter(|(_, e)| time <= released + e.gesture.time().unwrap_or_default())
+                .map(|(i, _)| (i, time))
+                .collect::<Vec<(usize, Instant)>>();
+
+            if double_taps.is_empty() {
+                r.extend(self.fire("Tap", time));
+            } else {
+                self.consumed = true;
+                r.extend(double_taps);
+            }
+        }
+
+        r
+    }
+
+    /// Advances the state machine with the release of one of the keys.
+    ///
+    /// Returns the index of every entry that fired along with when it fired.
+    pub(crate) fn release_key(&mut self, time: Instant) -> Vec<(usize, Instant)> {
+        if self.down_since.is_none() {
+            return vec![];
+        }
+
+        // Long presses that were due before the release but have not been polled yet
+        let r = self.fire_long_presses(time);
+        self.down_since = None;
+        if self.consumed {
+            return r;
+        }
+
+        if self.double_tap_window().is_some() {
+            self.pending_tap = Some(time);
+            return r;
+        }
+
+        self.fire("Tap", time)
+    }
+
+    /// Fires everything that is due by `now` without a key event, i.e. long presses and taps that can no
+    /// longer become double taps.
+    pub(crate) fn expire(&mut self, now: Instant) -> Vec<(usize, Instant)> {
+        let mut r = self.flush_tap(now);
+        r.extend(self.fire_long_presses(now));
+
+        r
+    }
+
+    /// When `expire` needs to be called next. `None` if nothing is pending.
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        let tap = match (self.pending_tap, self.double_tap_window()) {
+            (Some(released), Some(window)) => Some(released + window),
+            _ => None,
+        };
+
+        let long_press = match self.down_since {
+            Some(since) => self
+                .entries
+                .iter()
+                .filter(|e| !e.fired && matches!(e.gesture, Gesture::LongPress { .. }))
+                .filter_map(|e| e.gesture.time())
+                .map(|time| since + time)
+                .min(),
+            None => None,
+        };
+
+        tap.into_iter().chain(long_press).min()
+    }
+
+    /// Adds an action to be emitted when the gesture is performed. An action can only be bound to one
+    /// gesture of each kind.
+    pub(crate) fn add_action(&mut self, gesture: Gesture, action: &String) -> Result<()> {
+        if self
+            .entries
+            .iter()
+            .any(|e| e.gesture.as_str() == gesture.as_str() && e.actions.contains(action))
+        {
+            return Err(Error::ActionAlreadyExists {
+                action: action.clone(),
+                keys: format!("{} ({})", keys_to_string(&self.keys), gesture.as_str()),
+            });
+        }
+
+        match self.entries.iter_mut().find(|e| e.gesture == gesture) {
+            Some(e) => e.actions.push(action.clone()),
+            None => self.entries.push(GestureEntry {
+                gesture,
+                actions: vec![action.clone()],
+                payloads: HashMap::new(),
+                fired: false,
+            }),
+        }
+
+        Ok(())
+    }
+
+    /// Removes an action from the gesture of the same kind. The gesture's time is ignored.
+    pub(crate) fn remove_action(&mut self, gesture: Gesture, action: &String) -> Result<()> {
+        let e = match self.find_entry(gesture, action) {
+            Some(index) => &mut self.entries[index],
+            None => {
+                return Err(Error::ActionDoesNotExist {
+                    action: action.clone(),
+                    keys: format!("{} ({})", keys_to_string(&self.keys), gesture.as_str()),
+                    map_type: MapType::Gestures,
+                })
+            }
+        };
+
+        e.actions.retain(|a| a != action);
+        e.payloads.remove(action);
+        self.entries.retain(|e| !e.actions.is_empty());
+        self.pending_tap = None;
+
+        Ok(())
+    }
+
+    /// Returns the index of the entry of the same kind of gesture that contains an action. The gesture's time
+    /// is ignored.
+    pub(crate) fn find_entry(&self, gesture: Gesture, action: &String) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|e| e.gesture.as_str() == gesture.as_str() && e.actions.contains(action))
+    }
+}
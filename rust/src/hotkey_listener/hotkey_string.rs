@@ -0,0 +1,194 @@
+use std::str::FromStr;
+
+use livesplit_hotkey::KeyCode;
+
+use crate::hotkey_listener::{Error, Key, Result, ALL_KEY_CODES};
+
+/// Parses a hotkey string like `Ctrl+Shift+A`, `Super+F5` or `Ctrl+K, Ctrl+S` into steps of key names that
+/// can be passed to `HotkeyListener::register_action` (one step) or `HotkeyListener::register_sequence`.
+///
+/// Keys within a step are separated by `+` and steps by `,`. Key names are case-insensitive. Besides every
+/// name accepted by `Key::from_str`, common aliases are accepted, e.g. `Ctrl`, `Win`, `Cmd`, `LShift`,
+/// `Esc`, `PgUp`, `Up`, single letters and digits.
+pub fn parse_hotkey(hotkey: &str) -> Result<Vec<Vec<String>>> {
+    let mut steps = vec![];
+    for step in hotkey.split(',') {
+        let mut keys = vec![];
+        for token in step.split('+') {
+            let token = token.trim();
+            if token.is_empty() {
+                return Err(Error::EmptySequence);
+            }
+
+            keys.push(parse_key(token)?.name());
+        }
+        steps.push(keys);
+    }
+
+    Ok(steps)
+}
+
+/// Formats steps of key names as a canonical hotkey string that can be parsed back with `parse_hotkey`,
+/// e.g. `[["ShiftLeft", "KeyA", "Control"]]` becomes `Ctrl+LeftShift+A`.
+///
+/// Modifiers come first in the order Control, Shift, Alt, Super, followed by every other key sorted by name.
+pub fn format_hotkey(steps: &[Vec<String>]) -> Result<String> {
+    if steps.is_empty() || steps.iter().any(|s| s.is_empty()) {
+        return Err(Error::EmptySequence);
+    }
+
+    let mut formatted = vec![];
+    for step in steps.iter() {
+        let mut keys = vec![];
+        for name in step.iter() {
+            match Key::from_str(name) {
+                Ok(key) => keys.push(key),
+                Err(_) => return Err(Error::BadKeyCodeName(name.clone())),
+            }
+        }
+        keys.sort_by_cached_key(|k| (modifier_order(k), k.name()));
+        keys.dedup();
+
+        formatted.push(
+            keys.iter()
+                .map(display_name)
+                .collect::<Vec<String>>()
+                .join("+"),
+        );
+    }
+
+    Ok(formatted.join(", "))
+}
+
+/// Parses a single key, trying aliases first.
+fn parse_key(token: &str) -> Result<Key> {
+    let lower = token.to_ascii_lowercase();
+
+    let key = match lower.as_str() {
+        "ctrl" | "control" => Key::Control,
+        "shift" => Key::Shift,
+        "alt" | "option" | "opt" => Key::Alt,
+        "super" | "win" | "windows" | "meta" | "cmd" | "command" => Key::Super,
+
+        "lctrl" | "leftctrl" | "lcontrol" | "leftcontrol" | "controlleft" => {
+            KeyCode::ControlLeft.into()
+        }
+        "rctrl" | "rightctrl" | "rcontrol" | "rightcontrol" | "controlright" => {
+            KeyCode::ControlRight.into()
+        }
+        "lshift" | "leftshift" | "shiftleft" => KeyCode::ShiftLeft.into(),
+        "rshift" | "rightshift" | "shiftright" => KeyCode::ShiftRight.into(),
+        "lalt" | "leftalt" | "altleft" => KeyCode::AltLeft.into(),
+        "ralt" | "rightalt" | "altright" | "altgr" => KeyCode::AltRight.into(),
+        "lsuper" | "leftsuper" | "lwin" | "leftwin" | "lmeta" | "metaleft" => {
+            KeyCode::MetaLeft.into()
+        }
+        "rsuper" | "rightsuper" | "rwin" | "rightwin" | "rmeta" | "metaright" => {
+            KeyCode::MetaRight.into()
+        }
+
+        "esc" | "escape" => KeyCode::Escape.into(),
+        "enter" | "return" => KeyCode::Enter.into(),
+        "del" | "delete" => KeyCode::Delete.into(),
+        "ins" | "insert" => KeyCode::Insert.into(),
+        "pgup" | "pageup" => KeyCode::PageUp.into(),
+        "pgdn" | "pagedown" => KeyCode::PageDown.into(),
+        "up" => KeyCode::ArrowUp.into(),
+        "down" => KeyCode::ArrowDown.into(),
+        "left" => KeyCode::ArrowLeft.into(),
+        "right" => KeyCode::ArrowRight.into(),
+        "menu" => KeyCode::ContextMenu.into(),
+        "prtsc" | "printscreen" => KeyCode::PrintScreen.into(),
+
+        "`" => KeyCode::Backquote.into(),
+        "-" => KeyCode::Minus.into(),
+        "=" => KeyCode::Equal.into(),
+        "[" => KeyCode::BracketLeft.into(),
+        "]" => KeyCode::BracketRight.into(),
+        "\\" => KeyCode::Backslash.into(),
+        ";" => KeyCode::Semicolon.into(),
+        "'" => KeyCode::Quote.into(),
+        "." => KeyCode::Period.into(),
+        "/" => KeyCode::Slash.into(),
+
+        _ => return parse_key_name(token, &lower),
+    };
+
+    Ok(key)
+}
+
+/// Parses single letters, digits, numpad digits and any `Key` name, ignoring case.
+fn parse_key_name(token: &str, lower: &str) -> Result<Key> {
+    let mut chars = lower.chars();
+    let name = match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_lowercase() => format!("Key{}", c.to_ascii_uppercase()),
+        (Some(c), None) if c.is_ascii_digit() => format!("Digit{c}"),
+        _ => match lower.strip_prefix("num") {
+            Some(rest) if rest.len() == 1 && rest.chars().all(|c| c.is_ascii_digit()) => {
+                format!("Numpad{rest}")
+            }
+            _ => token.to_string(),
+        },
+    };
+
+    if let Ok(key) = Key::from_str(&name) {
+        return Ok(key);
+    }
+
+    match ALL_KEY_CODES
+        .iter()
+        .map(|k| Key::from(*k))
+        .chain([Key::Control, Key::Shift, Key::Alt, Key::Super])
+        .find(|k| k.name().eq_ignore_ascii_case(&name))
+    {
+        Some(key) => Ok(key),
+        None => Err(Error::BadKeyCodeName(token.to_string())),
+    }
+}
+
+/// Sorts modifiers before every other key.
+fn modifier_order(key: &Key) -> u8 {
+    match key {
+        Key::Control | Key::Code(KeyCode::ControlLeft) | Key::Code(KeyCode::ControlRight) => 0,
+        Key::Shift | Key::Code(KeyCode::ShiftLeft) | Key::Code(KeyCode::ShiftRight) => 1,
+        Key::Alt | Key::Code(KeyCode::AltLeft) | Key::Code(KeyCode::AltRight) => 2,
+        Key::Super | Key::Code(KeyCode::MetaLeft) | Key::Code(KeyCode::MetaRight) => 3,
+        _ => 4,
+    }
+}
+
+/// Returns the canonical name of a key in a hotkey string.
+fn display_name(key: &Key) -> String {
+    let alias = match key {
+        Key::Control => "Ctrl",
+        Key::Shift => "Shift",
+        Key::Alt => "Alt",
+        Key::Super => "Super",
+        Key::Code(KeyCode::ControlLeft) => "LeftCtrl",
+        Key::Code(KeyCode::ControlRight) => "RightCtrl",
+        Key::Code(KeyCode::ShiftLeft) => "LeftShift",
+        Key::Code(KeyCode::ShiftRight) => "RightShift",
+        Key::Code(KeyCode::AltLeft) => "LeftAlt",
+        Key::Code(KeyCode::AltRight) => "RightAlt",
+        Key::Code(KeyCode::MetaLeft) => "LeftSuper",
+        Key::Code(KeyCode::MetaRight) => "RightSuper",
+        Key::Code(KeyCode::ArrowUp) => "Up",
+        Key::Code(KeyCode::ArrowDown) => "Down",
+        Key::Code(KeyCode::ArrowLeft) => "Left",
+        Key::Code(KeyCode::ArrowRight) => "Right",
+        Key::Code(KeyCode::Escape) => "Esc",
+        _ => "",
+    };
+    if !alias.is_empty() {
+        return alias.to_string();
+    }
+
+    let name = key.name();
+    match name
+        .strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+    {
+        Some(rest) if rest.len() == 1 => rest.to_string(),
+        _ => name,
+    }
+}
@@ -0,0 +1,95 @@
+use std::{collections::HashSet, str::FromStr};
+
+use livesplit_hotkey::KeyCode;
+
+use crate::hotkey_listener::key_code_name;
+
+/// A key in a binding. Either a single physical key, or a modifier that is matched by both its left and
+/// right key.
+///
+/// Parsed from and formatted as the `KeyCode` variant name, e.g. `KeyA` or `ControlLeft`, or as one of
+/// `Control`, `Shift`, `Alt` or `Super` for the side-agnostic modifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    Code(KeyCode),
+    /// `ControlLeft` or `ControlRight`.
+    Control,
+    /// `ShiftLeft` or `ShiftRight`.
+    Shift,
+    /// `AltLeft` or `AltRight`.
+    Alt,
+    /// `MetaLeft` or `MetaRight`.
+    Super,
+}
+
+impl Key {
+    /// Returns every physical key that matches this key.
+    pub fn key_codes(&self) -> Vec<KeyCode> {
+        match self {
+            Key::Code(code) => vec![*code],
+            Key::Control => vec![KeyCode::ControlLeft, KeyCode::ControlRight],
+            Key::Shift => vec![KeyCode::ShiftLeft, KeyCode::ShiftRight],
+            Key::Alt => vec![KeyCode::AltLeft, KeyCode::AltRight],
+            Key::Super => vec![KeyCode::MetaLeft, KeyCode::MetaRight],
+        }
+    }
+
+    /// Checks if pressing the physical key `code` counts as pressing this key.
+    pub fn matches(&self, code: &KeyCode) -> bool {
+        self.key_codes().contains(code)
+    }
+
+    /// Checks if pressing `other` always counts as pressing this key, e.g. `ControlLeft` for `Control`.
+    pub fn is_implied_by(&self, other: &Key) -> bool {
+        other.key_codes().iter().all(|code| self.matches(code))
+    }
+
+    /// Checks if some physical key matches both this key and `other`, e.g. `Control` and `ControlLeft`.
+    pub fn overlaps(&self, other: &Key) -> bool {
+        other.key_codes().iter().any(|code| self.matches(code))
+    }
+
+    /// Checks if any physical key that matches this key is in the set of `held` keys.
+    pub fn is_held(&self, held: &HashSet<KeyCode>) -> bool {
+        self.key_codes().iter().any(|code| held.contains(code))
+    }
+
+    /// Returns the name of the key that can be parsed back with `Key::from_str`.
+    pub fn name(&self) -> String {
+        match self {
+            Key::Code(code) => key_code_name(code),
+            Key::Control => "Control".to_string(),
+            Key::Shift => "Shift".to_string(),
+            Key::Alt => "Alt".to_string(),
+            Key::Super => "Super".to_string(),
+        }
+    }
+}
+
+/// Checks if pressing all of `keys` always counts as pressing every key in `subset`, e.g. `ControlLeft+KeyA`
+/// for `Control+KeyA`.
+pub(crate) fn keys_imply(keys: &[Key], subset: &[Key]) -> bool {
+    subset
+        .iter()
+        .all(|k| keys.iter().any(|other| k.is_implied_by(other)))
+}
+
+impl From<KeyCode> for Key {
+    fn from(code: KeyCode) -> Self {
+        Key::Code(code)
+    }
+}
+
+impl FromStr for Key {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "Control" => Ok(Key::Control),
+            "Shift" => Ok(Key::Shift),
+            "Alt" => Ok(Key::Alt),
+            "Super" => Ok(Key::Super),
+            _ => KeyCode::from_str(s).map(Key::Code).map_err(|_| ()),
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use livesplit_hotkey::KeyCode;
+
+/// Every `KeyCode` known to livesplit-hotkey, in declaration order. Used by capture-all mode, see
+/// `HotkeyListener::set_capture_all`.
+pub const ALL_KEY_CODES: &[KeyCode] = &[
+    // Writing System Keys
+    KeyCode::Backquote,
+    KeyCode::Backslash,
+    KeyCode::Backspace,
+    KeyCode::BracketLeft,
+    KeyCode::BracketRight,
+    KeyCode::Comma,
+    KeyCode::Digit0,
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+    KeyCode::Equal,
+    KeyCode::IntlBackslash,
+    KeyCode::IntlRo,
+    KeyCode::IntlYen,
+    KeyCode::KeyA,
+    KeyCode::KeyB,
+    KeyCode::KeyC,
+    KeyCode::KeyD,
+    KeyCode::KeyE,
+    KeyCode::KeyF,
+    KeyCode::KeyG,
+    KeyCode::KeyH,
+    KeyCode::KeyI,
+    KeyCode::KeyJ,
+    KeyCode::KeyK,
+    KeyCode::KeyL,
+    KeyCode::KeyM,
+    KeyCode::KeyN,
+    KeyCode::KeyO,
+    KeyCode::KeyP,
+    KeyCode::KeyQ,
+    KeyCode::KeyR,
+    KeyCode::KeyS,
+    KeyCode::KeyT,
+    KeyCode::KeyU,
+    KeyCode::KeyV,
+    KeyCode::KeyW,
+    KeyCode::KeyX,
+    KeyCode::KeyY,
+    KeyCode::KeyZ,
+    KeyCode::Minus,
+    KeyCode::Period,
+    KeyCode::Quote,
+    KeyCode::Semicolon,
+    KeyCode::Slash,
+    // Functional Keys
+    KeyCode::AltLeft,
+    KeyCode::AltRight,
+    KeyCode::CapsLock,
+    KeyCode::ContextMenu,
+    KeyCode::ControlLeft,
+    KeyCode::ControlRight,
+    KeyCode::Enter,
+    KeyCode::MetaLeft,
+    KeyCode::MetaRight,
+    KeyCode::ShiftLeft,
+    KeyCode::ShiftRight,
+    KeyCode::Space,
+    KeyCode::Tab,
+    // Functional Keys found on Japanese and Korean keyboards
+    KeyCode::Convert,
+    KeyCode::KanaMode,
+    KeyCode::Lang1,
+    KeyCode::Lang2,
+    KeyCode::Lang3,
+    KeyCode::Lang4,
+    KeyCode::Lang5,
+    KeyCode::NonConvert,
+    // Control Pad Section
+    KeyCode::Delete,
+    KeyCode::End,
+    KeyCode::Help,
+    KeyCode::Home,
+    KeyCode::Insert,
+    KeyCode::PageDown,
+    KeyCode::PageUp,
+    // Arrow Pad Section
+    KeyCode::ArrowDown,
+    KeyCode::ArrowLeft,
+    KeyCode::ArrowRight,
+    KeyCode::ArrowUp,
+    // Numpad Section
+    KeyCode::NumLock,
+    KeyCode::Numpad0,
+    KeyCode::Numpad1,
+    KeyCode::Numpad2,
+    KeyCode::Numpad3,
+    KeyCode::Numpad4,
+    KeyCode::Numpad5,
+    KeyCode::Numpad6,
+    KeyCode::Numpad7,
+    KeyCode::Numpad8,
+    KeyCode::Numpad9,
+    KeyCode::NumpadAdd,
+    KeyCode::NumpadBackspace,
+    KeyCode::NumpadClear,
+    KeyCode::NumpadClearEntry,
+    KeyCode::NumpadComma,
+    KeyCode::NumpadDecimal,
+    KeyCode::NumpadDivide,
+    KeyCode::NumpadEnter,
+    KeyCode::NumpadEqual,
+    KeyCode::NumpadHash,
+    KeyCode::NumpadMemoryAdd,
+    KeyCode::NumpadMemoryClear,
+    KeyCode::NumpadMemoryRecall,
+    KeyCode::NumpadMemoryStore,
+    KeyCode::NumpadMemorySubtract,
+    KeyCode::NumpadMultiply,
+    KeyCode::NumpadParenLeft,
+    KeyCode::NumpadParenRight,
+    KeyCode::NumpadStar,
+    KeyCode::NumpadSubtract,
+    // Function Section
+    KeyCode::Escape,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+    KeyCode::F13,
+    KeyCode::F14,
+    KeyCode::F15,
+    KeyCode::F16,
+    KeyCode::F17,
+    KeyCode::F18,
+    KeyCode::F19,
+    KeyCode::F20,
+    KeyCode::F21,
+    KeyCode::F22,
+    KeyCode::F23,
+    KeyCode::F24,
+    KeyCode::Fn,
+    KeyCode::FnLock,
+    KeyCode::PrintScreen,
+    KeyCode::ScrollLock,
+    KeyCode::Pause,
+    // Media Keys
+    KeyCode::BrowserBack,
+    KeyCode::BrowserFavorites,
+    KeyCode::BrowserForward,
+    KeyCode::BrowserHome,
+    KeyCode::BrowserRefresh,
+    KeyCode::BrowserSearch,
+    KeyCode::BrowserStop,
+    KeyCode::Eject,
+    KeyCode::LaunchApp1,
+    KeyCode::LaunchApp2,
+    KeyCode::LaunchMail,
+    KeyCode::MediaPlayPause,
+    KeyCode::MediaSelect,
+    KeyCode::MediaStop,
+    KeyCode::MediaTrackNext,
+    KeyCode::MediaTrackPrevious,
+    KeyCode::Power,
+    KeyCode::Sleep,
+    KeyCode::AudioVolumeDown,
+    KeyCode::AudioVolumeMute,
+    KeyCode::AudioVolumeUp,
+    KeyCode::WakeUp,
+    // Legacy, Non-Standard and Special Keys
+    KeyCode::Again,
+    KeyCode::Copy,
+    KeyCode::Cut,
+    KeyCode::Find,
+    KeyCode::Open,
+    KeyCode::Paste,
+    KeyCode::Props,
+    KeyCode::Select,
+    KeyCode::Undo,
+    // Gamepad Keys
+    KeyCode::Gamepad0,
+    KeyCode::Gamepad1,
+    KeyCode::Gamepad2,
+    KeyCode::Gamepad3,
+    KeyCode::Gamepad4,
+    KeyCode::Gamepad5,
+    KeyCode::Gamepad6,
+    KeyCode::Gamepad7,
+    KeyCode::Gamepad8,
+    KeyCode::Gamepad9,
+    KeyCode::Gamepad10,
+    KeyCode::Gamepad11,
+    KeyCode::Gamepad12,
+    KeyCode::Gamepad13,
+    KeyCode::Gamepad14,
+    KeyCode::Gamepad15,
+    KeyCode::Gamepad16,
+    KeyCode::Gamepad17,
+    KeyCode::Gamepad18,
+    KeyCode::Gamepad19,
+    // Chrome only Keys
+    KeyCode::BrightnessDown,
+    KeyCode::BrightnessUp,
+    KeyCode::DisplayToggleIntExt,
+    KeyCode::KeyboardLayoutSelect,
+    KeyCode::LaunchAssistant,
+    KeyCode::LaunchControlPanel,
+    KeyCode::LaunchScreenSaver,
+    KeyCode::MailForward,
+    KeyCode::MailReply,
+    KeyCode::MailSend,
+    KeyCode::MediaFastForward,
+    KeyCode::MediaPause,
+    KeyCode::MediaPlay,
+    KeyCode::MediaRecord,
+    KeyCode::MediaRewind,
+    KeyCode::PrivacyScreenToggle,
+    KeyCode::SelectTask,
+    KeyCode::ShowAllWindows,
+    KeyCode::ZoomToggle,
+];
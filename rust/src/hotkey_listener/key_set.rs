@@ -0,0 +1,92 @@
+use std::{fmt, ops::Deref, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::hotkey_listener::{keys_to_string, Error, Key, Result};
+
+/// Identifies a key sequence. Keys are sorted by name and duplicates are removed, so two key sets are equal
+/// exactly when they contain the same keys, regardless of the order the keys were given in.
+///
+/// Serialized as an array of key names, e.g. `["Control", "KeyS"]`, and displayed as `Control+KeyS`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "Vec<String>", try_from = "Vec<String>")]
+pub struct KeySet(Vec<Key>);
+
+impl KeySet {
+    pub fn new<I: IntoIterator<Item = Key>>(keys: I) -> Self {
+        let mut keys = keys.into_iter().collect::<Vec<Key>>();
+        keys.sort_by_cached_key(Key::name);
+        keys.dedup();
+
+        KeySet(keys)
+    }
+
+    /// Parses every key name, see `Key::from_str`. A side-agnostic modifier cannot be combined with one of its
+    /// sides, e.g. `Control` with `ControlLeft`.
+    pub fn from_names(names: &[String]) -> Result<Self> {
+        let mut keys = vec![];
+        for name in names.iter() {
+            match Key::from_str(name) {
+                Ok(k) => keys.push(k),
+                Err(_) => return Err(Error::BadKeyCodeName(name.clone())),
+            }
+        }
+
+        let key_set = KeySet::new(keys);
+        for (i, key) in key_set.iter().enumerate() {
+            if let Some(other) = key_set[i + 1..].iter().find(|other| key.overlaps(other)) {
+                return Err(Error::OverlappingKeys(key.name(), other.name()));
+            }
+        }
+
+        Ok(key_set)
+    }
+
+    /// Returns the name of every key, sorted.
+    pub fn names(&self) -> Vec<String> {
+        self.0.iter().map(Key::name).collect()
+    }
+}
+
+impl Deref for KeySet {
+    type Target = [Key];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<[Key]> for KeySet {
+    fn as_ref(&self) -> &[Key] {
+        &self.0
+    }
+}
+
+impl<'a> IntoIterator for &'a KeySet {
+    type Item = &'a Key;
+    type IntoIter = std::slice::Iter<'a, Key>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl fmt::Display for KeySet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", keys_to_string(&self.0))
+    }
+}
+
+impl From<KeySet> for Vec<String> {
+    fn from(key_set: KeySet) -> Self {
+        key_set.names()
+    }
+}
+
+impl TryFrom<Vec<String>> for KeySet {
+    type Error = Error;
+
+    fn try_from(names: Vec<String>) -> Result<Self> {
+        KeySet::from_names(&names)
+    }
+}
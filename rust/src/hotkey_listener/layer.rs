@@ -0,0 +1,113 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use livesplit_hotkey::KeyCode;
+
+use crate::hotkey_listener::{keys_to_string, ActionMapping, Error, Key, KeySet, MapType, Result};
+
+/// A named table of key sequences that only apply while the layer is active, see
+/// `HotkeyListener::register_layer`.
+#[derive(Debug)]
+pub(crate) struct Layer {
+    /// Pressing the key sequence enters the layer. `None` if the layer can only be entered with `push_layer`.
+    /// Its actions are never emitted.
+    pub(crate) leader: Option<ActionMapping>,
+    /// How long the layer stays active without a key press handled by a layer. `None` to stay active
+    /// until popped.
+    pub(crate) timeout: Option<Duration>,
+
+    pub(crate) actions: HashMap<KeySet, ActionMapping>,
+    pub(crate) lookup: HashMap<KeyCode, Vec<KeySet>>,
+}
+
+impl Layer {
+    pub(crate) fn new(leader: Option<ActionMapping>, timeout: Option<Duration>) -> Self {
+        Layer {
+            leader,
+            timeout,
+
+            actions: HashMap::new(),
+            lookup: HashMap::new(),
+        }
+    }
+
+    /// Checks if `key` is part of the leader.
+    pub(crate) fn is_leader_key(&self, key: &KeyCode) -> bool {
+        match self.leader.as_ref() {
+            Some(am) => am.keys.keys().any(|k| k.matches(key)),
+            None => false,
+        }
+    }
+
+    /// Adds an action to the key sequence `keys`, creating the key sequence if needed.
+    pub(crate) fn add_action(
+        &mut self,
+        action_name: &String,
+        keys: &[Key],
+        key_set: &KeySet,
+        registration: u64,
+    ) -> Result<()> {
+        match self.actions.get_mut(key_set) {
+            Some(am) => am.add_action(action_name)?,
+            None => {
+                let mut am = ActionMapping::new(keys, registration);
+                am.add_action(action_name)?;
+                self.actions.insert(key_set.clone(), am);
+            }
+        }
+
+        for key in keys.iter().flat_map(Key::key_codes) {
+            let v = self.lookup.entry(key).or_default();
+            if !v.contains(key_set) {
+                v.push(key_set.clone());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes an action from the key sequence `keys`, removing the key sequence once it has no actions left.
+    pub(crate) fn remove_action(
+        &mut self,
+        action_name: &String,
+        keys: &[Key],
+        key_set: &KeySet,
+    ) -> Result<()> {
+        let am = match self.actions.get_mut(key_set) {
+            Some(am) => am,
+            None => {
+                return Err(Error::ActionDoesNotExist {
+                    action: action_name.clone(),
+                    keys: keys_to_string(keys),
+                    map_type: MapType::Layers,
+                })
+            }
+        };
+        am.remove_action(action_name)?;
+        if !am.actions.is_empty() {
+            return Ok(());
+        }
+
+        self.actions.remove(key_set);
+        for key in keys.iter().flat_map(Key::key_codes) {
+            if let Some(v) = self.lookup.get_mut(&key) {
+                v.retain(|k| k != key_set);
+                if v.is_empty() {
+                    self.lookup.remove(&key);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A layer on the layer stack.
+#[derive(Debug)]
+pub(crate) struct ActiveLayer {
+    pub(crate) name: String,
+    /// When the layer was entered or last handled a key press.
+    pub(crate) last_activity: Instant,
+}
@@ -0,0 +1,195 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use livesplit_hotkey::KeyCode;
+
+use crate::hotkey_listener::{steps_to_string, ChordMode, Error, Key, MapType, Payload, Result};
+
+/// The outcome of feeding a key press into a `SequenceMapping`.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum SequenceProgress {
+    /// The key press did not affect the sequence.
+    Unchanged,
+    /// A step was completed. Contains the number of completed steps.
+    Advanced(usize),
+    /// Every step was completed. The sequence starts over afterwards.
+    Completed,
+    /// A wrong key was pressed after at least one step was completed.
+    Reset,
+}
+
+/// Stores all actions associated with an ordered list of chords along with how far along the
+/// list the user currently is.
+#[derive(Debug)]
+pub(crate) struct SequenceMapping {
+    pub(crate) actions: Vec<String>,
+    pub(crate) steps: Vec<Vec<Key>>,
+    /// Data delivered with the events of each action.
+    pub(crate) payloads: HashMap<String, Payload>,
+
+    /// Number of steps that have already been completed.
+    progress: usize,
+    /// When the last step was completed.
+    last_progress: Instant,
+    /// Last-pressed time for each key pressed during the current step.
+    pressed: HashMap<Key, Instant>,
+    /// Keys that completed the previous step and are also part of the current step. These do not
+    /// need to be pressed again, e.g. holding Control for both steps of `Ctrl+K, Ctrl+S`.
+    carried: HashSet<Key>,
+}
+
+impl SequenceMapping {
+    pub(crate) fn new(steps: Vec<Vec<Key>>) -> Self {
+        SequenceMapping {
+            actions: vec![],
+            steps,
+            payloads: HashMap::new(),
+
+            progress: 0,
+            last_progress: Instant::now(),
+            pressed: HashMap::new(),
+            carried: HashSet::new(),
+        }
+    }
+
+    /// Returns every key used by any step. Keys are not deduplicated.
+    pub(crate) fn keys(&self) -> impl Iterator<Item = &Key> {
+        self.steps.iter().flatten()
+    }
+
+    /// Returns every key used by any step in the order they first appear, without duplicates.
+    pub(crate) fn unique_keys(&self) -> Vec<Key> {
+        let mut r: Vec<Key> = vec![];
+        for key in self.keys() {
+            if !r.contains(key) {
+                r.push(*key);
+            }
+        }
+
+        r
+    }
+
+    /// Number of steps that have already been completed.
+    pub(crate) fn progress(&self) -> usize {
+        self.progress
+    }
+
+    /// Whether at least one step has been completed but the sequence was not finished within
+    /// `step_timeout` by `now`.
+    pub(crate) fn is_expired(&self, step_timeout: &Duration, now: Instant) -> bool {
+        self.progress > 0 && now.saturating_duration_since(self.last_progress) > *step_timeout
+    }
+
+    /// When the sequence will expire if no further step is completed. `None` if no step has been completed.
+    pub(crate) fn deadline(&self, step_timeout: &Duration) -> Option<Instant> {
+        if self.progress == 0 {
+            return None;
+        }
+
+        Some(self.last_progress + *step_timeout)
+    }
+
+    /// Starts the sequence over from the first step.
+    pub(crate) fn reset(&mut self) {
+        self.progress = 0;
+        self.pressed.clear();
+        self.carried.clear();
+    }
+
+    /// Advances the state machine with a key press observed at `time`.
+    ///
+    /// Pressing a key that is not part of the current step resets the sequence. The key is then
+    /// checked against the first step so that a wrong key can also start the sequence over.
+    pub(crate) fn press_key(
+        &mut self,
+        key: &KeyCode,
+        time: Instant,
+        chord_mode: ChordMode,
+        min_elapsed_time: &Duration,
+        held_keys: &HashSet<KeyCode>,
+    ) -> SequenceProgress {
+        if !self.steps[self.progress].iter().any(|k| k.matches(key)) {
+            if self.progress == 0 {
+                return SequenceProgress::Unchanged;
+            }
+
+            self.reset();
+            self.press_key(key, time, chord_mode, min_elapsed_time, held_keys);
+
+            return SequenceProgress::Reset;
+        }
+
+        for k in self.steps[self.progress].iter().filter(|k| k.matches(key)) {
+            self.pressed.insert(*k, time);
+        }
+
+        let is_step_pressed = self.steps[self.progress].iter().all(|k| match chord_mode {
+            ChordMode::Timed => {
+                self.carried.contains(k)
+                    || match self.pressed.get(k) {
+                        Some(pressed_at) => {
+                            time.saturating_duration_since(*pressed_at) <= *min_elapsed_time
+                        }
+                        None => false,
+                    }
+            }
+            ChordMode::Held => k.is_held(held_keys),
+        });
+        if !is_step_pressed {
+            return SequenceProgress::Unchanged;
+        }
+
+        self.progress += 1;
+        if self.progress == self.steps.len() {
+            self.reset();
+            return SequenceProgress::Completed;
+        }
+
+        self.carried = self.steps[self.progress - 1]
+            .iter()
+            .filter(|k| self.steps[self.progress].contains(k))
+            .copied()
+            .collect();
+        self.pressed.clear();
+        self.last_progress = time;
+
+        SequenceProgress::Advanced(self.progress)
+    }
+
+    /// A released key can no longer be carried over into the current step.
+    pub(crate) fn release_key(&mut self, key: &KeyCode) {
+        self.carried.retain(|k| !k.matches(key));
+    }
+
+    /// Adds an action to be emitted when every step is completed.
+    pub(crate) fn add_action(&mut self, action: &String) -> Result<()> {
+        if self.actions.contains(action) {
+            return Err(Error::ActionAlreadyExists {
+                action: action.clone(),
+                keys: steps_to_string(&self.steps),
+            });
+        }
+
+        self.actions.push(action.clone());
+
+        Ok(())
+    }
+
+    /// Removes an action to be emitted when every step is completed.
+    pub(crate) fn remove_action(&mut self, action: &String) -> Result<()> {
+        if !self.actions.contains(action) {
+            return Err(Error::ActionDoesNotExist {
+                action: action.clone(),
+                keys: steps_to_string(&self.steps),
+                map_type: MapType::Sequences,
+            });
+        }
+
+        self.actions.retain(|a| a != action);
+        self.payloads.remove(action);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    thread::{self, JoinHandle},
+    time::Instant,
+};
+
+use crossbeam_channel::{after, bounded, never, select, unbounded, Sender};
+
+use crate::hotkey_listener::HotkeyListener;
+
+/// Handle to a `HotkeyListener` that is being driven by its own worker thread.
+///
+/// The worker blocks until a key event arrives or a pending timer (e.g. a sequence timeout) expires, so
+/// `poll` never needs to be called manually. Events are delivered through the listener's `Sender` as usual.
+///
+/// Dropping the handle stops the worker.
+pub struct ListenerThread {
+    listener: Arc<Mutex<HotkeyListener>>,
+    shutdown_sender: Sender<()>,
+    /// Makes the worker recompute its timer, since changes made through `listener` can move the next deadline.
+    wake_sender: Sender<()>,
+    join_handle: Option<JoinHandle<()>>,
+}
+
+/// Exclusive access to the listener of a `ListenerThread`, see `ListenerThread::listener`.
+///
+/// Dropping the guard unlocks the listener and wakes the worker.
+pub struct ListenerGuard<'a> {
+    listener: MutexGuard<'a, HotkeyListener>,
+    wake_sender: &'a Sender<()>,
+}
+
+impl Deref for ListenerGuard<'_> {
+    type Target = HotkeyListener;
+
+    fn deref(&self) -> &Self::Target {
+        &self.listener
+    }
+}
+
+impl DerefMut for ListenerGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.listener
+    }
+}
+
+impl Drop for ListenerGuard<'_> {
+    fn drop(&mut self) {
+        // A wake-up that is already pending covers this one as well
+        self.wake_sender.try_send(()).ok();
+    }
+}
+
+impl ListenerThread {
+    pub(crate) fn spawn(listener: HotkeyListener) -> Self {
+        let key_receiver = listener.callback_receiver.clone();
+        let listener = Arc::new(Mutex::new(listener));
+        let (shutdown_sender, shutdown_receiver) = unbounded::<()>();
+        let (wake_sender, wake_receiver) = bounded::<()>(1);
+
+        let worker_listener = listener.clone();
+        let join_handle = thread::spawn(move || loop {
+            let timer = match worker_listener
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .next_deadline()
+            {
+                Some(deadline) => after(deadline.saturating_duration_since(Instant::now())),
+                None => never(),
+            };
+
+            select! {
+                recv(shutdown_receiver) -> _ => break,
+                recv(wake_receiver) -> _ => {},
+                recv(key_receiver) -> event => {
+                    let mut listener = worker_listener.lock().unwrap_or_else(PoisonError::into_inner);
+                    match event {
+                        Ok(event) => listener.process_key_event(event),
+                        Err(e) => {
+                            eprintln!("{e}");
+                            break;
+                        }
+                    }
+                    listener.poll();
+                },
+                recv(timer) -> _ => {
+                    worker_listener.lock().unwrap_or_else(PoisonError::into_inner).poll();
+                },
+            }
+        });
+
+        ListenerThread {
+            listener,
+            shutdown_sender,
+            wake_sender,
+            join_handle: Some(join_handle),
+        }
+    }
+
+    /// Locks the listener so that actions can be registered, unregistered or configured.
+    ///
+    /// The worker cannot process key events while the lock is held. Once the guard is dropped, the worker
+    /// picks up any new deadline, e.g. of a layer pushed with `push_layer`.
+    pub fn listener(&self) -> ListenerGuard<'_> {
+        ListenerGuard {
+            listener: self.listener.lock().unwrap_or_else(PoisonError::into_inner),
+            wake_sender: &self.wake_sender,
+        }
+    }
+
+    /// Stops the worker and waits for it to finish.
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        if let Some(handle) = self.join_handle.take() {
+            self.shutdown_sender.send(()).ok();
+            handle.join().ok();
+        }
+    }
+}
+
+impl Drop for ListenerThread {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
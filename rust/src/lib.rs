@@ -1,5 +1,6 @@
 #[cfg(feature = "gdnative")]
 mod godot;
 
+pub mod backend;
 pub mod hotkey_listener;
 pub use hotkey_listener::HotkeyListener;
@@ -0,0 +1,22 @@
+mod common;
+
+use common::{drain, pressed, setup, sv};
+use livesplit_hotkey::KeyCode;
+
+#[test]
+fn scripted_backend_hooks_registered_keys() {
+    let (mut l, kb, r) = setup();
+    let keys = sv(&["KeyA", "KeyB"]);
+    l.register_action(&"X".into(), &keys).unwrap();
+    assert_eq!(kb.hooked_keys(), vec![KeyCode::KeyA, KeyCode::KeyB]);
+    assert!(!kb.press(KeyCode::KeyC, 0));
+
+    kb.press(KeyCode::KeyA, 0);
+    kb.press(KeyCode::KeyB, 10);
+    l.poll();
+    assert_eq!(drain(&r), vec![pressed("X")]);
+
+    l.unregister_action(&"X".into(), &keys).unwrap();
+    assert!(kb.hooked_keys().is_empty());
+    assert!(!kb.press(KeyCode::KeyA, 20));
+}
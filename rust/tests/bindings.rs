@@ -0,0 +1,414 @@
+mod common;
+
+use common::{drain, event, pressed, released, setup, setup_with, sv};
+use hotkey_listener::{
+    backend::ScriptedBackend,
+    hotkey_listener::{
+        ActionEventKind, AutoRepeat, Bindings, Conflict, Error, Gesture, KeySet, ResolutionPolicy,
+    },
+};
+use livesplit_hotkey::KeyCode;
+
+#[test]
+fn export_and_import() {
+    let (mut l, kb, _r) = setup();
+    l.register_action(&"X".into(), &sv(&["KeyA", "ShiftLeft"]))
+        .unwrap();
+    l.register_sequence(&"S".into(), &[sv(&["KeyA"]), sv(&["KeyB"])])
+        .unwrap();
+    let doc = Bindings::from_toml(&l.export_bindings().to_toml().unwrap()).unwrap();
+    assert_eq!(doc, l.export_bindings());
+
+    let bad = Bindings::from_toml(
+        "[[actions.Y]]\nkeys=[\"Nope\"]\n[[actions.Y]]\n\n[[actions.Z]]\nkeys=[\"KeyC\"]",
+    )
+    .unwrap();
+    match l.import_bindings(&bad) {
+        Err(Error::InvalidBindings(v)) => assert_eq!(v.len(), 2),
+        e => panic!("{e:?}"),
+    }
+    assert_eq!(l.export_bindings(), doc);
+
+    let good = Bindings::from_toml("[[actions.Z]]\nkeys=[\"KeyC\"]\norder=0").unwrap();
+    l.import_bindings(&good).unwrap();
+    assert_eq!(l.export_bindings(), good);
+    assert_eq!(kb.hooked_keys(), vec![KeyCode::KeyC]);
+}
+
+#[test]
+fn failed_import_restores_previous_bindings() {
+    let (mut l, kb, r) = setup_with(ScriptedBackend::without_releases());
+    l.register_action(&"X".into(), &sv(&["KeyA"])).unwrap();
+    let previous = l.export_bindings();
+    kb.press(KeyCode::KeyA, 0);
+    l.poll();
+    assert_eq!(drain(&r), vec![pressed("X")]);
+
+    // Valid on its own, but the backend cannot repeat
+    let doc = Bindings::from_toml(
+        "[[actions.Y]]\nkeys=[\"KeyB\"]\n\
+         [[actions.Z]]\nkeys=[\"KeyC\"]\nrepeat={delay=0.5,interval=0.1}",
+    )
+    .unwrap();
+    assert!(doc.validate().is_ok());
+    assert!(matches!(
+        l.import_bindings(&doc),
+        Err(Error::ReleasesNotReported(_))
+    ));
+    assert_eq!(l.export_bindings(), previous);
+    assert_eq!(kb.hooked_keys(), vec![KeyCode::KeyA]);
+
+    // The restored key sequence is still active, and is only released by an import that succeeds
+    assert!(drain(&r).is_empty());
+    l.import_bindings(&Bindings::default()).unwrap();
+    assert_eq!(drain(&r), vec![released("X")]);
+}
+
+#[test]
+fn key_set_options_only_apply_to_keys() {
+    let doc = Bindings::from_toml(
+        "[[actions.X]]\nsteps=[[\"KeyA\"],[\"KeyB\"]]\npriority=1\n\
+         [[actions.X]]\nsteps=[[\"KeyC\"],[\"KeyD\"]]\norder=0\n\
+         [[actions.X]]\nkeys=[\"KeyE\"]\ngesture={kind=\"Tap\"}\nmin_elapsed_time=0.1\n\
+         [[actions.X]]\nkeys=[\"KeyF\"]\ngesture={kind=\"Tap\"}\nrepeat={delay=0.5,interval=0.1}\n\
+         [[actions.X]]\nkeys=[\"KeyG\"]\npriority=1\norder=0\nmin_elapsed_time=0.1",
+    )
+    .unwrap();
+    let errors = doc.validate().unwrap_err();
+    assert_eq!(
+        errors.iter().map(|e| e.index).collect::<Vec<usize>>(),
+        vec![0, 1, 2, 3]
+    );
+    assert!(errors
+        .iter()
+        .all(|e| matches!(e.error, Error::InvalidBinding)));
+}
+
+#[test]
+fn import_releases_active_bindings() {
+    let (mut l, kb, r) = setup();
+    l.register_action(&"X".into(), &sv(&["KeyA"])).unwrap();
+    l.register_sequence(&"S".into(), &[sv(&["KeyB"]), sv(&["KeyC"])])
+        .unwrap();
+    l.set_sequence_timeout(60.0).unwrap();
+
+    kb.press(KeyCode::KeyA, 59_000);
+    kb.press(KeyCode::KeyB, 59_010);
+    l.poll();
+    assert_eq!(
+        drain(&r),
+        vec![
+            pressed("X"),
+            event("S", ActionEventKind::SequenceAdvanced(1))
+        ]
+    );
+
+    l.import_bindings(&Bindings::default()).unwrap();
+    assert_eq!(
+        drain(&r),
+        vec![released("X"), event("S", ActionEventKind::SequenceReset)]
+    );
+}
+
+#[test]
+fn import_keeps_registration_order() {
+    let (mut l, kb, r) = setup();
+    l.set_resolution_policy(ResolutionPolicy::FirstRegistered);
+    l.register_action(&"Z".into(), &sv(&["Control", "KeyA"]))
+        .unwrap();
+    l.register_action(&"A".into(), &sv(&["Control", "ShiftLeft", "KeyA"]))
+        .unwrap();
+    let toml = l.export_bindings().to_toml().unwrap();
+    assert!(toml.contains("order = 1"), "{toml}");
+
+    l.import_bindings(&Bindings::default()).unwrap();
+    l.import_bindings(&Bindings::from_toml(&toml).unwrap())
+        .unwrap();
+    kb.press(KeyCode::ControlLeft, 0);
+    kb.press(KeyCode::ShiftLeft, 10);
+    kb.press(KeyCode::KeyA, 20);
+    l.poll();
+    assert_eq!(drain(&r), vec![pressed("Z")]);
+}
+
+#[test]
+fn save_and_load() {
+    let (mut l, kb, _r) = setup();
+    l.register_action(&"Z".into(), &sv(&["KeyC"])).unwrap();
+    let doc = l.export_bindings();
+    let path = std::env::temp_dir().join(format!("hotkey_listener_{}.toml", std::process::id()));
+    l.save_bindings(&path).unwrap();
+
+    l.import_bindings(&Bindings::default()).unwrap();
+    assert!(kb.hooked_keys().is_empty());
+    l.load_bindings(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(l.export_bindings(), doc);
+}
+
+#[test]
+fn profiles() {
+    let (mut l, kb, r) = setup();
+    l.register_action(&"X".into(), &sv(&["KeyA", "ShiftLeft"]))
+        .unwrap();
+    l.create_profile(&"edit".into()).unwrap();
+    assert_eq!(l.get_profiles(), sv(&["default", "edit"]));
+
+    l.set_active_profile(&"edit".into()).unwrap();
+    assert!(kb.hooked_keys().is_empty());
+    l.register_action(&"Y".into(), &sv(&["KeyA"])).unwrap();
+    kb.press(KeyCode::KeyA, 0);
+    l.poll();
+    assert_eq!(drain(&r), vec![pressed("Y")]);
+
+    l.set_active_profile(&"default".into()).unwrap();
+    assert_eq!(kb.hooked_keys(), vec![KeyCode::KeyA, KeyCode::ShiftLeft]);
+    assert!(l.remove_profile(&"default".into()).is_err());
+    l.set_active_profile(&"edit".into()).unwrap();
+    assert_eq!(kb.hooked_keys(), vec![KeyCode::KeyA]);
+    l.remove_profile(&"default".into()).unwrap();
+    assert_eq!(l.get_profiles(), sv(&["edit"]));
+}
+
+#[test]
+fn conflicts() {
+    let (mut l, _kb, _r) = setup();
+    l.register_action(&"X".into(), &sv(&["KeyA", "ControlLeft"]))
+        .unwrap();
+    l.register_action(&"Y".into(), &sv(&["KeyA", "ControlLeft"]))
+        .unwrap();
+    l.register_action(&"Z".into(), &sv(&["KeyA", "ControlLeft", "ShiftLeft"]))
+        .unwrap();
+    l.register_action(&"W".into(), &sv(&["KeyB", "ControlLeft"]))
+        .unwrap();
+
+    let c = l.find_conflicts();
+    assert!(matches!(c[0], Conflict::Duplicate(_)));
+    let count = |f: fn(&Conflict) -> bool| c.iter().filter(|x| f(x)).count();
+    assert_eq!(count(|x| matches!(x, Conflict::Overlap { .. })), 1);
+    assert_eq!(count(|x| matches!(x, Conflict::SharedKeys { .. })), 2);
+}
+
+#[test]
+fn conflicts_with_modifier_aliases() {
+    let (mut l, _kb, _r) = setup();
+    l.register_action(&"S".into(), &sv(&["Control", "KeyA"]))
+        .unwrap();
+    l.register_action(&"L".into(), &sv(&["ControlLeft", "ShiftLeft", "KeyA"]))
+        .unwrap();
+    l.register_action(&"R".into(), &sv(&["ControlRight", "KeyB"]))
+        .unwrap();
+
+    let c = l.find_conflicts();
+    assert_eq!(c.len(), 2, "{c:?}");
+    match &c[0] {
+        Conflict::Overlap { subset, superset } => {
+            assert_eq!(subset.actions, sv(&["S"]));
+            assert_eq!(superset.actions, sv(&["L"]));
+        }
+        other => panic!("{other:?}"),
+    }
+    match &c[1] {
+        Conflict::SharedKeys { shared, .. } => assert_eq!(shared.len(), 1),
+        other => panic!("{other:?}"),
+    }
+
+    let e = l
+        .register_action(&"X".into(), &sv(&["Control", "ControlLeft", "KeyA"]))
+        .unwrap_err();
+    assert_eq!(e.code(), "OverlappingKeys");
+    assert!(KeySet::from_names(&sv(&["Shift", "ShiftRight"])).is_err());
+}
+
+#[test]
+fn error_context() {
+    let (mut l, _kb, _r) = setup();
+    let e = l
+        .register_action(&"X".into(), &sv(&["KeyA", "Bogus"]))
+        .unwrap_err();
+    assert_eq!(e.code(), "BadKeyCodeName");
+    assert!(e.to_string().contains("Bogus"));
+
+    l.register_action(&"X".into(), &sv(&["KeyA", "Control"]))
+        .unwrap();
+    let e = l
+        .register_action(&"X".into(), &sv(&["Control", "KeyA"]))
+        .unwrap_err();
+    assert!(
+        matches!(&e, Error::ActionAlreadyExists { action, keys } if action == "X" && keys == "Control+KeyA")
+    );
+    let e = l
+        .unregister_action(&"Y".into(), &sv(&["KeyA", "Control"]))
+        .unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "action `Y` is not bound to the key set `Control+KeyA`"
+    );
+    let e = l.get_binding_priority(&sv(&["KeyB"])).unwrap_err();
+    assert_eq!(e.code(), "BindingDoesNotExist");
+
+    let e = l.load_bindings("/nonexistent/bindings.toml").unwrap_err();
+    assert!(std::error::Error::source(&e).is_some());
+    assert!(e.to_string().contains("/nonexistent/bindings.toml"));
+}
+
+#[test]
+fn validate_after_unregistering() {
+    let (mut l, _kb, _r) = setup();
+    l.register_action(&"A".into(), &sv(&["Control", "ShiftLeft", "KeyA"]))
+        .unwrap();
+    l.register_sequence(
+        &"S".into(),
+        &[sv(&["Control", "KeyK"]), sv(&["Control", "KeyS"])],
+    )
+    .unwrap();
+    l.register_gesture(&"G".into(), &sv(&["Control", "ShiftLeft"]), Gesture::Tap)
+        .unwrap();
+    l.register_layer(&"L".into(), &sv(&["F1"]), None).unwrap();
+    l.register_layer_action(&"L".into(), &"LA".into(), &sv(&["KeyW"]))
+        .unwrap();
+    l.push_layer(&"L".into()).unwrap();
+    assert!(l.validate().is_ok());
+
+    l.unregister_action(&"A".into(), &sv(&["Control", "ShiftLeft", "KeyA"]))
+        .unwrap();
+    l.unregister_sequence(
+        &"S".into(),
+        &[sv(&["Control", "KeyK"]), sv(&["Control", "KeyS"])],
+    )
+    .unwrap();
+    l.unregister_gesture(&"G".into(), &sv(&["Control", "ShiftLeft"]), Gesture::Tap)
+        .unwrap();
+    l.unregister_layer_action(&"L".into(), &"LA".into(), &sv(&["KeyW"]))
+        .unwrap();
+    l.unregister_layer(&"L".into()).unwrap();
+    assert!(l.validate().is_ok());
+}
+
+#[test]
+fn key_set_identity() {
+    let a = KeySet::from_names(&sv(&["KeyS", "Control", "KeyS"])).unwrap();
+    let b = KeySet::from_names(&sv(&["Control", "KeyS"])).unwrap();
+    assert_eq!(a, b);
+    assert_eq!(a.to_string(), "Control+KeyS");
+    assert_eq!(a.names(), sv(&["Control", "KeyS"]));
+
+    let (mut l, kb, r) = setup();
+    l.register_action(&"Save".into(), &sv(&["KeyS", "Control"]))
+        .unwrap();
+    assert!(l
+        .register_action(&"Save".into(), &sv(&["Control", "KeyS", "KeyS"]))
+        .is_err());
+    l.unregister_action(&"Save".into(), &sv(&["Control", "KeyS"]))
+        .unwrap();
+    assert!(kb.hooked_keys().is_empty());
+    assert!(r.is_empty());
+    assert!(l.validate().is_ok());
+}
+
+#[test]
+fn invalid_durations_are_refused() {
+    let (mut l, kb, _r) = setup();
+    let f = sv(&["F1"]);
+    l.register_action(&"X".into(), &f).unwrap();
+    let is_invalid = |r: Result<(), Error>| matches!(r, Err(Error::InvalidDuration { .. }));
+
+    assert!(is_invalid(l.set_min_elapsed_time(-1.0)));
+    assert!(is_invalid(l.set_sequence_timeout(f32::NAN)));
+    assert!(is_invalid(
+        l.set_action_cooldown(&"X".into(), Some(f32::INFINITY))
+    ));
+    assert!(is_invalid(l.set_binding_min_elapsed_time(&f, Some(-0.5))));
+    assert!(is_invalid(l.set_binding_repeat(
+        &f,
+        Some(AutoRepeat {
+            delay: 0.5,
+            interval: 0.0
+        })
+    )));
+    assert!(is_invalid(l.register_layer(
+        &"L".into(),
+        &sv(&["F2"]),
+        Some(-1.0)
+    )));
+    assert!(is_invalid(l.register_gesture(
+        &"G".into(),
+        &f,
+        Gesture::LongPress { time: f32::NAN }
+    )));
+    assert_eq!(l.get_min_elapsed_time(), 0.2);
+    assert!(l.validate().is_ok());
+
+    // Nothing is left half-registered, and polling does not panic
+    kb.press(KeyCode::F1, 0);
+    l.poll();
+    assert_eq!(l.get_action_names(), sv(&["X"]));
+}
+
+#[test]
+fn huge_durations_are_refused() {
+    let (mut l, kb, _r) = setup();
+    let f = sv(&["F1"]);
+    l.register_action(&"X".into(), &f).unwrap();
+    let is_invalid = |r: Result<(), Error>| matches!(r, Err(Error::InvalidDuration { .. }));
+
+    assert!(is_invalid(l.set_min_elapsed_time(1e19)));
+    assert!(is_invalid(l.set_sequence_timeout(1e19)));
+    assert!(is_invalid(l.set_action_cooldown(&"X".into(), Some(1e19))));
+    assert!(is_invalid(l.set_binding_min_elapsed_time(&f, Some(1e19))));
+    assert!(is_invalid(l.set_binding_repeat(
+        &f,
+        Some(AutoRepeat {
+            delay: 1e19,
+            interval: 0.1
+        })
+    )));
+    assert!(is_invalid(l.set_binding_repeat(
+        &f,
+        Some(AutoRepeat {
+            delay: 0.1,
+            interval: 1e19
+        })
+    )));
+    assert!(is_invalid(l.register_layer(
+        &"L".into(),
+        &sv(&["F2"]),
+        Some(1e19)
+    )));
+    assert!(is_invalid(l.register_gesture(
+        &"G".into(),
+        &f,
+        Gesture::LongPress { time: 1e19 }
+    )));
+    assert!(is_invalid(l.register_gesture(
+        &"G".into(),
+        &f,
+        Gesture::DoubleTap { time: 1e19 }
+    )));
+
+    // A year is still accepted, and computing its deadlines does not overflow
+    l.set_sequence_timeout(365.0 * 24.0 * 60.0 * 60.0).unwrap();
+    l.register_sequence(&"S".into(), &[sv(&["F1"]), sv(&["F3"])])
+        .unwrap();
+    kb.press(KeyCode::F1, 0);
+    l.poll();
+    assert!(l.next_deadline().is_some());
+}
+
+#[test]
+fn invalid_durations_in_documents() {
+    let (mut l, _kb, _r) = setup();
+    let doc = Bindings::from_toml(
+        "[[actions.X]]\nkeys=[\"KeyA\"]\nmin_elapsed_time=-1.0\n\
+         [[actions.X]]\nkeys=[\"KeyB\"]\nrepeat={delay=0.5,interval=-0.1}\n\
+         [[actions.X]]\nkeys=[\"KeyC\"]\ngesture={kind=\"DoubleTap\",time=-2.0}",
+    )
+    .unwrap();
+    let errors = doc.validate().unwrap_err();
+    assert_eq!(errors.len(), 3);
+    assert!(errors
+        .iter()
+        .all(|e| matches!(e.error, Error::InvalidDuration { .. })));
+    assert!(l.import_bindings(&doc).is_err());
+    assert!(l.get_action_names().is_empty());
+}
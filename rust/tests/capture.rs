@@ -0,0 +1,56 @@
+mod common;
+
+use common::{setup, sv};
+use hotkey_listener::hotkey_listener::ALL_KEY_CODES;
+use livesplit_hotkey::KeyCode;
+
+#[test]
+fn capture_all_hooks_every_key() {
+    let (mut l, kb, _r) = setup();
+    let (sender, receiver) = crossbeam_channel::unbounded();
+    l.set_key_sender(Some(sender));
+    l.register_action(&"X".into(), &sv(&["KeyA"])).unwrap();
+
+    l.set_capture_all(true).unwrap();
+    assert_eq!(kb.hooked_keys().len(), ALL_KEY_CODES.len());
+    kb.press(KeyCode::KeyZ, 0);
+    l.poll();
+    assert!(l.is_key_held(&KeyCode::KeyZ));
+    assert_eq!(receiver.try_recv().unwrap().key, KeyCode::KeyZ);
+
+    l.set_capture_all(false).unwrap();
+    assert_eq!(kb.hooked_keys(), vec![KeyCode::KeyA]);
+}
+
+#[test]
+fn capture_ends_when_every_key_is_released() {
+    let (mut l, kb, r) = setup();
+    l.register_action(&"X".into(), &sv(&["KeyA"])).unwrap();
+    l.set_sequence_timeout(60.0).unwrap();
+
+    l.start_capture().unwrap();
+    assert!(l.is_capturing());
+    kb.press(KeyCode::ControlRight, 59_000);
+    kb.press(KeyCode::KeyA, 59_010);
+    kb.release(KeyCode::KeyA, 59_020);
+    l.poll();
+    assert!(r.is_empty());
+    assert!(l.take_capture().is_none());
+
+    kb.release(KeyCode::ControlRight, 59_030);
+    l.poll();
+    assert!(!l.is_capturing());
+    assert_eq!(l.take_capture(), Some(sv(&["ControlRight", "KeyA"])));
+    assert_eq!(kb.hooked_keys(), vec![KeyCode::KeyA]);
+}
+
+#[test]
+fn capture_times_out_without_releases() {
+    let (mut l, kb, _r) = setup();
+    l.set_sequence_timeout(0.05).unwrap();
+
+    l.start_capture().unwrap();
+    kb.press(KeyCode::KeyB, 0);
+    l.poll();
+    assert_eq!(l.take_capture(), Some(sv(&["KeyB"])));
+}
@@ -0,0 +1,422 @@
+mod common;
+
+use common::{drain, event, pressed, released, setup, setup_with, sv};
+use hotkey_listener::{
+    backend::{KeyState, ScriptedBackend},
+    hotkey_listener::{
+        ActionEventKind, AutoRepeat, ChordMode, Error, Key, Payload, ResolutionPolicy,
+    },
+};
+use livesplit_hotkey::KeyCode;
+
+#[test]
+fn held_chord_requires_every_key_down() {
+    let (mut l, kb, r) = setup();
+    l.set_chord_mode(ChordMode::Held).unwrap();
+    l.register_action(&"X".into(), &sv(&["KeyA", "ControlLeft"]))
+        .unwrap();
+
+    kb.press(KeyCode::ControlLeft, 0);
+    kb.release(KeyCode::ControlLeft, 10);
+    kb.press(KeyCode::KeyA, 20);
+    l.poll();
+    assert!(drain(&r).is_empty());
+
+    kb.press(KeyCode::ControlLeft, 30);
+    kb.release(KeyCode::KeyA, 40);
+    kb.release(KeyCode::ControlLeft, 50);
+    l.poll();
+    assert_eq!(drain(&r), vec![pressed("X"), released("X")]);
+}
+
+#[test]
+fn held_chord_requires_releases() {
+    let (mut l, kb, r) = setup_with(ScriptedBackend::without_releases());
+    assert!(matches!(
+        l.set_chord_mode(ChordMode::Held),
+        Err(Error::ReleasesNotReported(_))
+    ));
+    assert_eq!(l.get_chord_mode(), ChordMode::Timed);
+    l.register_action(&"X".into(), &sv(&["KeyA", "ControlLeft"]))
+        .unwrap();
+
+    // Control was pressed too long ago, no matter that it was never released
+    kb.press(KeyCode::ControlLeft, 0);
+    kb.press(KeyCode::KeyA, 300);
+    l.poll();
+    assert!(drain(&r).is_empty());
+    assert!(!l.is_key_held(&KeyCode::ControlLeft));
+
+    // Every press counts, none of them is an OS repeat
+    kb.press(KeyCode::ControlLeft, 310);
+    kb.press(KeyCode::KeyA, 1000);
+    kb.press(KeyCode::ControlLeft, 1010);
+    l.poll();
+    assert_eq!(drain(&r), vec![pressed("X"), pressed("X")]);
+}
+
+#[test]
+fn edge_triggered_requires_releases() {
+    let (mut l, kb, r) = setup_with(ScriptedBackend::without_releases());
+    assert!(matches!(
+        l.set_edge_triggered(true),
+        Err(Error::ReleasesNotReported(_))
+    ));
+    assert!(!l.get_edge_triggered());
+    l.set_edge_triggered(false).unwrap();
+    l.register_action(&"X".into(), &sv(&["F1"])).unwrap();
+
+    kb.press(KeyCode::F1, 0);
+    kb.press(KeyCode::F1, 10_000);
+    kb.press(KeyCode::F1, 20_000);
+    l.poll();
+    assert_eq!(drain(&r), vec![pressed("X"), pressed("X"), pressed("X")]);
+}
+
+#[test]
+fn min_elapsed_time_uses_event_timestamps() {
+    let (mut l, kb, r) = setup();
+    l.register_action(&"X".into(), &sv(&["KeyA", "KeyB"]))
+        .unwrap();
+    l.set_min_elapsed_time(0.1).unwrap();
+
+    kb.press(KeyCode::KeyA, 0);
+    kb.press(KeyCode::KeyB, 150);
+    l.poll();
+    assert!(drain(&r).is_empty());
+
+    kb.press(KeyCode::KeyA, 200);
+    l.poll();
+    assert_eq!(drain(&r), vec![pressed("X")]);
+}
+
+#[test]
+fn binding_min_elapsed_time_overrides_global() {
+    let (mut l, kb, r) = setup();
+    l.register_action(&"X".into(), &sv(&["KeyA", "KeyB"]))
+        .unwrap();
+    l.set_min_elapsed_time(0.0).unwrap();
+
+    kb.press(KeyCode::KeyA, 0);
+    kb.press(KeyCode::KeyB, 20);
+    l.poll();
+    assert!(drain(&r).is_empty());
+
+    l.set_binding_min_elapsed_time(&sv(&["KeyB", "KeyA"]), Some(1.0))
+        .unwrap();
+    assert_eq!(
+        l.get_binding_min_elapsed_time(&sv(&["KeyA", "KeyB"]))
+            .unwrap(),
+        Some(1.0)
+    );
+    kb.press(KeyCode::KeyA, 30);
+    l.poll();
+    assert_eq!(drain(&r), vec![pressed("X")]);
+    assert_eq!(
+        l.export_bindings().actions["X"][0].min_elapsed_time,
+        Some(1.0)
+    );
+}
+
+#[test]
+fn events_carry_serials_and_keys() {
+    let (mut l, kb, r) = setup();
+    l.register_action(&"X".into(), &sv(&["KeyA"])).unwrap();
+    l.register_action(&"Y".into(), &sv(&["KeyA"])).unwrap();
+
+    kb.press(KeyCode::KeyA, 0);
+    l.poll();
+    let first = r.try_recv().unwrap();
+    let second = r.try_recv().unwrap();
+    assert_eq!((first.serial, second.serial), (0, 1));
+    assert_eq!(first.keys, vec![KeyCode::KeyA.into()]);
+    assert_eq!(first.timestamp, kb.at(0));
+}
+
+#[test]
+fn max_events_per_poll() {
+    let (mut l, kb, r) = setup();
+    l.register_action(&"X".into(), &sv(&["KeyA"])).unwrap();
+    for i in 0..5 {
+        kb.press(KeyCode::KeyA, i * 10);
+    }
+
+    l.set_max_events_per_poll(Some(2));
+    assert_eq!(l.poll(), 2);
+    l.set_max_events_per_poll(None);
+    assert_eq!(l.poll(), 3);
+    assert_eq!(r.len(), 5);
+}
+
+#[test]
+fn resolution_policies() {
+    let (mut l, kb, r) = setup();
+    l.set_chord_mode(ChordMode::Held).unwrap();
+    l.register_action(&"S".into(), &sv(&["ControlLeft", "KeyA"]))
+        .unwrap();
+    l.register_action(&"L".into(), &sv(&["ControlLeft", "ShiftLeft", "KeyA"]))
+        .unwrap();
+    let chord = |l: &mut hotkey_listener::HotkeyListener, start: u64| {
+        kb.press(KeyCode::ControlLeft, start);
+        kb.press(KeyCode::ShiftLeft, start + 10);
+        kb.press(KeyCode::KeyA, start + 20);
+        kb.release(KeyCode::KeyA, start + 30);
+        kb.release(KeyCode::ShiftLeft, start + 40);
+        kb.release(KeyCode::ControlLeft, start + 50);
+        l.poll();
+        let mut fired = r
+            .try_iter()
+            .filter(|e| e.kind == ActionEventKind::Pressed)
+            .map(|e| e.action)
+            .collect::<Vec<String>>();
+        fired.sort();
+        fired
+    };
+
+    assert_eq!(chord(&mut l, 0), sv(&["L", "S"]));
+    l.set_resolution_policy(ResolutionPolicy::LongestMatch);
+    assert_eq!(chord(&mut l, 100), sv(&["L"]));
+    l.set_resolution_policy(ResolutionPolicy::FirstRegistered);
+    assert_eq!(chord(&mut l, 200), sv(&["S"]));
+    l.set_resolution_policy(ResolutionPolicy::Priority);
+    l.set_binding_priority(&sv(&["KeyA", "ControlLeft"]), 5)
+        .unwrap();
+    assert_eq!(chord(&mut l, 300), sv(&["S"]));
+    assert_eq!(l.export_bindings().actions["S"][0].priority, 5);
+}
+
+#[test]
+fn longest_match_with_modifier_aliases() {
+    let (mut l, kb, r) = setup();
+    l.set_resolution_policy(ResolutionPolicy::LongestMatch);
+    l.register_action(&"S".into(), &sv(&["Control", "KeyA"]))
+        .unwrap();
+    l.register_action(&"L".into(), &sv(&["ControlLeft", "ShiftLeft", "KeyA"]))
+        .unwrap();
+
+    kb.press(KeyCode::ControlLeft, 0);
+    kb.press(KeyCode::ShiftLeft, 10);
+    kb.press(KeyCode::KeyA, 20);
+    l.poll();
+    assert_eq!(drain(&r), vec![pressed("L")]);
+
+    // The alias is matched by the other side as well, which the longer binding does not cover
+    kb.press(KeyCode::ControlRight, 1000);
+    kb.press(KeyCode::KeyA, 1010);
+    l.poll();
+    assert_eq!(drain(&r), vec![pressed("S")]);
+}
+
+#[test]
+fn modifier_aliases_match_either_side() {
+    let (mut l, kb, r) = setup();
+    l.set_chord_mode(ChordMode::Held).unwrap();
+    l.register_action(&"C".into(), &sv(&["Control", "KeyC"]))
+        .unwrap();
+    let mut names = l.get_key_names();
+    names.sort();
+    assert_eq!(names, sv(&["Control", "KeyC"]));
+    assert_eq!(
+        kb.hooked_keys(),
+        vec![KeyCode::ControlLeft, KeyCode::ControlRight, KeyCode::KeyC]
+    );
+
+    kb.press(KeyCode::ControlRight, 0);
+    kb.press(KeyCode::KeyC, 10);
+    l.poll();
+    let e = r.try_recv().unwrap();
+    assert_eq!(e.kind, ActionEventKind::Pressed);
+    assert_eq!(e.keys, vec![Key::Control, KeyCode::KeyC.into()]);
+
+    // The alias stays held while either side is down
+    l.set_edge_triggered(true).unwrap();
+    kb.press(KeyCode::ControlLeft, 20);
+    kb.release(KeyCode::ControlRight, 30);
+    l.poll();
+    assert!(drain(&r).is_empty());
+    kb.release(KeyCode::ControlLeft, 40);
+    kb.release(KeyCode::KeyC, 50);
+    l.poll();
+    assert_eq!(drain(&r), vec![released("C")]);
+
+    l.unregister_action(&"C".into(), &sv(&["KeyC", "Control"]))
+        .unwrap();
+    assert!(kb.hooked_keys().is_empty());
+}
+
+#[test]
+fn edge_triggered_and_cooldown() {
+    let (mut l, kb, r) = setup();
+    let f = sv(&["F3"]);
+    l.register_action(&"E".into(), &f).unwrap();
+
+    kb.press(KeyCode::F3, 0);
+    kb.press(KeyCode::F3, 10);
+    kb.release(KeyCode::F3, 20);
+    l.poll();
+    assert_eq!(drain(&r), vec![pressed("E"), pressed("E"), released("E")]);
+
+    l.set_edge_triggered(true).unwrap();
+    kb.press(KeyCode::F3, 100);
+    kb.press(KeyCode::F3, 110);
+    kb.release(KeyCode::F3, 120);
+    kb.press(KeyCode::F3, 130);
+    kb.release(KeyCode::F3, 140);
+    l.poll();
+    assert_eq!(
+        drain(&r),
+        vec![pressed("E"), released("E"), pressed("E"), released("E")]
+    );
+
+    l.set_action_cooldown(&"E".into(), Some(10.0)).unwrap();
+    assert_eq!(l.get_action_cooldown(&"E".into()), Some(10.0));
+    kb.press(KeyCode::F3, 200);
+    kb.release(KeyCode::F3, 210);
+    kb.press(KeyCode::F3, 220);
+    kb.release(KeyCode::F3, 230);
+    l.poll();
+    assert_eq!(drain(&r), vec![pressed("E"), released("E")]);
+}
+
+#[test]
+fn cooldown_drops_the_whole_press() {
+    let (mut l, kb, r) = setup();
+    let f = sv(&["F2"]);
+    l.register_action(&"R".into(), &f).unwrap();
+    l.set_binding_repeat(
+        &f,
+        Some(AutoRepeat {
+            delay: 0.1,
+            interval: 0.05,
+        }),
+    )
+    .unwrap();
+    l.set_action_cooldown(&"R".into(), Some(10.0)).unwrap();
+
+    kb.press(KeyCode::F2, 0);
+    kb.release(KeyCode::F2, 10);
+    l.poll();
+    assert_eq!(drain(&r), vec![pressed("R"), released("R")]);
+
+    // Held long enough to repeat, but the press came too soon after the last one
+    kb.press(KeyCode::F2, 20);
+    l.poll();
+    kb.release(KeyCode::F2, 30);
+    l.poll();
+    assert!(drain(&r).is_empty());
+
+    l.set_action_cooldown(&"R".into(), None).unwrap();
+    kb.press(KeyCode::F2, 40);
+    kb.release(KeyCode::F2, 50);
+    l.poll();
+    assert_eq!(drain(&r), vec![pressed("R"), released("R")]);
+}
+
+#[test]
+fn auto_repeat_while_held() {
+    let (mut l, kb, r) = setup();
+    let f = sv(&["F2"]);
+    l.register_action(&"R".into(), &f).unwrap();
+    l.set_binding_repeat(
+        &f,
+        Some(AutoRepeat {
+            delay: 0.1,
+            interval: 0.05,
+        }),
+    )
+    .unwrap();
+
+    // The OS repeat is swallowed, and the overdue repeat fires once
+    kb.press(KeyCode::F2, 0);
+    kb.press(KeyCode::F2, 10);
+    l.poll();
+    assert_eq!(
+        drain(&r),
+        vec![pressed("R"), event("R", ActionEventKind::Repeat)]
+    );
+    assert!(l.next_deadline().is_some());
+
+    kb.release(KeyCode::F2, 20);
+    l.poll();
+    assert_eq!(drain(&r), vec![released("R")]);
+    assert!(l.next_deadline().is_none());
+    assert!(l.export_bindings().actions["R"][0].repeat.is_some());
+}
+
+#[test]
+fn auto_repeat_requires_releases() {
+    let (mut l, kb, r) = setup_with(ScriptedBackend::without_releases());
+    let f = sv(&["F2"]);
+    l.register_action(&"R".into(), &f).unwrap();
+    let repeat = AutoRepeat {
+        delay: 0.1,
+        interval: 0.05,
+    };
+    assert!(matches!(
+        l.set_binding_repeat(&f, Some(repeat)),
+        Err(Error::ReleasesNotReported(_))
+    ));
+    l.set_binding_repeat(&f, None).unwrap();
+
+    kb.press(KeyCode::F2, 0);
+    kb.press(KeyCode::F2, 10);
+    l.poll();
+    assert_eq!(drain(&r), vec![pressed("R"), pressed("R")]);
+    assert!(l.next_deadline().is_none());
+}
+
+#[test]
+fn raw_key_events_are_forwarded() {
+    let (mut l, kb, _r) = setup();
+    let (sender, receiver) = crossbeam_channel::unbounded();
+    l.set_key_sender(Some(sender));
+    l.register_action(&"X".into(), &sv(&["KeyA"])).unwrap();
+
+    kb.press(KeyCode::KeyA, 0);
+    kb.release(KeyCode::KeyA, 10);
+    l.poll();
+    let v = receiver
+        .try_iter()
+        .map(|e| (e.key, e.state))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        v,
+        vec![
+            (KeyCode::KeyA, KeyState::Pressed),
+            (KeyCode::KeyA, KeyState::Released)
+        ]
+    );
+}
+
+#[test]
+fn binding_payloads() {
+    let (mut l, kb, r) = setup();
+    l.set_chord_mode(ChordMode::Held).unwrap();
+    let a = "SetExpression".to_string();
+    l.register_action(&a, &sv(&["Digit1"])).unwrap();
+    l.register_action(&a, &sv(&["Digit2"])).unwrap();
+    l.set_binding_payload(&a, &sv(&["Digit1"]), Some(Payload::String("smile".into())))
+        .unwrap();
+    l.set_binding_payload(&a, &sv(&["Digit2"]), Some(Payload::Integer(2)))
+        .unwrap();
+    assert!(l
+        .set_binding_payload(&"Other".into(), &sv(&["Digit1"]), None)
+        .is_err());
+
+    kb.press(KeyCode::Digit2, 0);
+    kb.release(KeyCode::Digit2, 10);
+    kb.press(KeyCode::Digit1, 20);
+    l.poll();
+    let payloads = r.try_iter().map(|e| e.payload).collect::<Vec<_>>();
+    assert_eq!(
+        payloads,
+        vec![
+            Some(Payload::Integer(2)),
+            Some(Payload::Integer(2)),
+            Some(Payload::String("smile".into()))
+        ]
+    );
+
+    l.set_binding_payload(&a, &sv(&["Digit1"]), None).unwrap();
+    assert_eq!(l.get_binding_payload(&a, &sv(&["Digit1"])).unwrap(), None);
+}
@@ -0,0 +1,75 @@
+#![allow(dead_code)]
+
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::Receiver;
+use hotkey_listener::{
+    backend::{KeyEvent, ScriptedBackend},
+    hotkey_listener::{ActionEvent, ActionEventKind},
+    HotkeyListener,
+};
+use livesplit_hotkey::KeyCode;
+
+/// Scripts key events at fixed offsets from a start time in the past, so that everything the
+/// listener times out on `poll` has already expired, without the test having to sleep.
+pub struct Keyboard {
+    pub backend: ScriptedBackend,
+    start: Instant,
+}
+
+impl Keyboard {
+    /// Returns the time `ms` milliseconds after the start of the script.
+    pub fn at(&self, ms: u64) -> Instant {
+        self.start + Duration::from_millis(ms)
+    }
+
+    pub fn press(&self, key: KeyCode, ms: u64) -> bool {
+        self.backend.send(KeyEvent::pressed_at(key, self.at(ms)))
+    }
+
+    pub fn release(&self, key: KeyCode, ms: u64) -> bool {
+        self.backend.send(KeyEvent::released_at(key, self.at(ms)))
+    }
+
+    pub fn hooked_keys(&self) -> Vec<KeyCode> {
+        let mut keys = self.backend.hooked_keys();
+        keys.sort_by_key(|k| format!("{k:?}"));
+        keys
+    }
+}
+
+pub fn setup() -> (HotkeyListener, Keyboard, Receiver<ActionEvent>) {
+    setup_with(ScriptedBackend::new())
+}
+
+pub fn setup_with(backend: ScriptedBackend) -> (HotkeyListener, Keyboard, Receiver<ActionEvent>) {
+    let (sender, receiver) = crossbeam_channel::unbounded();
+    let listener = HotkeyListener::with_backend(sender, Box::new(backend.clone()));
+    let keyboard = Keyboard {
+        backend,
+        start: Instant::now() - Duration::from_secs(60),
+    };
+
+    (listener, keyboard, receiver)
+}
+
+pub fn sv(v: &[&str]) -> Vec<String> {
+    v.iter().map(|s| s.to_string()).collect()
+}
+
+/// Returns the action and kind of every event received so far.
+pub fn drain(receiver: &Receiver<ActionEvent>) -> Vec<(String, ActionEventKind)> {
+    receiver.try_iter().map(|e| (e.action, e.kind)).collect()
+}
+
+pub fn event(action: &str, kind: ActionEventKind) -> (String, ActionEventKind) {
+    (action.to_string(), kind)
+}
+
+pub fn pressed(action: &str) -> (String, ActionEventKind) {
+    event(action, ActionEventKind::Pressed)
+}
+
+pub fn released(action: &str) -> (String, ActionEventKind) {
+    event(action, ActionEventKind::Released)
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use common::{drain, pressed, setup, setup_with, sv};
+use hotkey_listener::{
+    backend::ScriptedBackend,
+    hotkey_listener::{Bindings, Error, Gesture, Payload},
+};
+use livesplit_hotkey::KeyCode;
+
+fn register(l: &mut hotkey_listener::HotkeyListener) -> Vec<String> {
+    let f = sv(&["F1"]);
+    l.register_gesture(&"T".into(), &f, Gesture::Tap).unwrap();
+    l.register_gesture(&"D".into(), &f, Gesture::DoubleTap { time: 0.1 })
+        .unwrap();
+    l.register_gesture(&"L".into(), &f, Gesture::LongPress { time: 0.1 })
+        .unwrap();
+    f
+}
+
+#[test]
+fn tap_waits_out_double_tap_window() {
+    let (mut l, kb, r) = setup();
+    let f = sv(&["F1"]);
+    l.register_gesture(&"T".into(), &f, Gesture::Tap).unwrap();
+    l.register_gesture(&"D".into(), &f, Gesture::DoubleTap { time: 60.0 })
+        .unwrap();
+    assert!(l.register_gesture(&"T".into(), &f, Gesture::Tap).is_err());
+
+    // Released just now, so the tap can still become a double tap for a minute
+    kb.press(KeyCode::F1, 60_000);
+    kb.release(KeyCode::F1, 60_010);
+    l.poll();
+    assert!(drain(&r).is_empty());
+    assert!(l.next_deadline().is_some());
+
+    kb.press(KeyCode::F1, 60_020);
+    kb.release(KeyCode::F1, 60_030);
+    l.poll();
+    assert_eq!(drain(&r), vec![pressed("D")]);
+    assert!(l.next_deadline().is_none());
+}
+
+#[test]
+fn double_tap() {
+    let (mut l, kb, r) = setup();
+    register(&mut l);
+
+    kb.press(KeyCode::F1, 0);
+    kb.release(KeyCode::F1, 10);
+    kb.press(KeyCode::F1, 20);
+    kb.release(KeyCode::F1, 30);
+    l.poll();
+    assert_eq!(drain(&r), vec![pressed("D")]);
+
+    // Too slow for a double tap, so two taps
+    kb.press(KeyCode::F1, 100);
+    kb.release(KeyCode::F1, 110);
+    kb.press(KeyCode::F1, 300);
+    kb.release(KeyCode::F1, 310);
+    l.poll();
+    assert_eq!(drain(&r), vec![pressed("T"), pressed("T")]);
+}
+
+#[test]
+fn long_press_fires_while_held() {
+    let (mut l, kb, r) = setup();
+    register(&mut l);
+
+    kb.press(KeyCode::F1, 0);
+    l.poll();
+    assert_eq!(drain(&r), vec![pressed("L")]);
+
+    kb.release(KeyCode::F1, 500);
+    l.poll();
+    assert!(drain(&r).is_empty());
+}
+
+#[test]
+fn gestures_round_trip() {
+    let (mut l, _kb, _r) = setup();
+    let f = register(&mut l);
+
+    let doc = l.export_bindings();
+    let toml = doc.to_toml().unwrap();
+    assert!(toml.contains("LongPress"), "{toml}");
+    let back = Bindings::from_toml(&toml).unwrap();
+    assert_eq!(back, doc);
+    l.import_bindings(&back).unwrap();
+
+    l.unregister_gesture(&"L".into(), &f, Gesture::LongPress { time: 0.0 })
+        .unwrap();
+    assert_eq!(l.get_action_names(), sv(&["D", "T"]));
+}
+
+#[test]
+fn gesture_payloads() {
+    let (mut l, kb, r) = setup();
+    let f = register(&mut l);
+    let t = "T".to_string();
+    l.set_gesture_payload(&t, &f, Gesture::Tap, Some(Payload::Integer(1)))
+        .unwrap();
+    assert!(matches!(
+        l.set_gesture_payload(&t, &f, Gesture::DoubleTap { time: 0.1 }, None),
+        Err(Error::ActionDoesNotExist { .. })
+    ));
+
+    kb.press(KeyCode::F1, 0);
+    kb.release(KeyCode::F1, 10);
+    l.poll();
+    let payloads = r.try_iter().map(|e| e.payload).collect::<Vec<_>>();
+    assert_eq!(payloads, vec![Some(Payload::Integer(1))]);
+
+    let doc = Bindings::from_toml(&l.export_bindings().to_toml().unwrap()).unwrap();
+    assert_eq!(doc.actions["T"][0].payload, Some(Payload::Integer(1)));
+    l.import_bindings(&doc).unwrap();
+    assert_eq!(
+        l.get_gesture_payload(&t, &f, Gesture::Tap).unwrap(),
+        Some(Payload::Integer(1))
+    );
+
+    l.unregister_gesture(&t, &f, Gesture::Tap).unwrap();
+    l.register_gesture(&t, &f, Gesture::Tap).unwrap();
+    assert_eq!(l.get_gesture_payload(&t, &f, Gesture::Tap).unwrap(), None);
+}
+
+#[test]
+fn gestures_require_releases() {
+    let (mut l, kb, _r) = setup_with(ScriptedBackend::without_releases());
+    let f = sv(&["F1"]);
+    assert!(matches!(
+        l.register_gesture(&"T".into(), &f, Gesture::Tap),
+        Err(Error::ReleasesNotReported(_))
+    ));
+    assert!(l.get_action_names().is_empty());
+
+    l.register_action(&"X".into(), &f).unwrap();
+    let doc = Bindings::from_toml("[[actions.T]]\nkeys=[\"F1\"]\ngesture={kind=\"Tap\"}").unwrap();
+    assert!(doc.validate().is_ok());
+    assert!(matches!(
+        l.import_bindings(&doc),
+        Err(Error::ReleasesNotReported(_))
+    ));
+    assert_eq!(l.get_action_names(), sv(&["X"]));
+    assert_eq!(kb.hooked_keys(), vec![KeyCode::F1]);
+}
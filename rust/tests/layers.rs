@@ -0,0 +1,144 @@
+mod common;
+
+use common::{drain, event, pressed, released, setup, setup_with, sv};
+use hotkey_listener::{
+    backend::ScriptedBackend,
+    hotkey_listener::{ActionEventKind, ChordMode, Error, Payload},
+};
+use livesplit_hotkey::KeyCode;
+
+#[test]
+fn leader_enters_and_escape_exits() {
+    let (mut l, kb, r) = setup();
+    l.set_chord_mode(ChordMode::Held).unwrap();
+    l.register_action(&"Base".into(), &sv(&["KeyW"])).unwrap();
+    l.register_layer(&"Win".into(), &sv(&["Control", "Space"]), None)
+        .unwrap();
+    l.register_layer_action(&"Win".into(), &"Split".into(), &sv(&["KeyW"]))
+        .unwrap();
+    assert!(!kb.hooked_keys().contains(&KeyCode::Escape));
+
+    kb.press(KeyCode::ControlLeft, 0);
+    kb.press(KeyCode::Space, 10);
+    l.poll();
+    assert_eq!(drain(&r), vec![event("Win", ActionEventKind::LayerEntered)]);
+    assert_eq!(l.get_layer_stack(), sv(&["Win"]));
+    assert!(kb.hooked_keys().contains(&KeyCode::Escape));
+
+    // Keys bound in the layer shadow the base bindings
+    kb.release(KeyCode::Space, 20);
+    kb.release(KeyCode::ControlLeft, 30);
+    kb.press(KeyCode::KeyW, 40);
+    kb.release(KeyCode::KeyW, 50);
+    kb.press(KeyCode::Escape, 60);
+    kb.release(KeyCode::Escape, 70);
+    kb.press(KeyCode::KeyW, 80);
+    kb.release(KeyCode::KeyW, 90);
+    l.poll();
+    assert_eq!(
+        drain(&r),
+        vec![
+            pressed("Split"),
+            released("Split"),
+            event("Win", ActionEventKind::LayerExited),
+            pressed("Base"),
+            released("Base")
+        ]
+    );
+    assert!(!kb.hooked_keys().contains(&KeyCode::Escape));
+
+    l.unregister_layer(&"Win".into()).unwrap();
+    l.unregister_action(&"Base".into(), &sv(&["KeyW"])).unwrap();
+    assert!(kb.hooked_keys().is_empty());
+}
+
+#[test]
+fn layers_without_releases() {
+    let (mut l, kb, r) = setup_with(ScriptedBackend::without_releases());
+    l.register_layer(&"Win".into(), &sv(&["F1"]), None).unwrap();
+    l.register_layer_action(&"Win".into(), &"Split".into(), &sv(&["KeyW"]))
+        .unwrap();
+
+    // Without releases, no press can be told apart from an OS repeat, so every press counts
+    for start in [0, 100] {
+        kb.press(KeyCode::F1, start);
+        l.poll();
+        kb.press(KeyCode::KeyW, start + 10);
+        kb.press(KeyCode::KeyW, start + 20);
+        kb.press(KeyCode::Escape, start + 30);
+        l.poll();
+    }
+    let once = [
+        event("Win", ActionEventKind::LayerEntered),
+        pressed("Split"),
+        pressed("Split"),
+        released("Split"),
+        event("Win", ActionEventKind::LayerExited),
+    ];
+    assert_eq!(drain(&r), [once.clone(), once].concat());
+    assert!(l.get_layer_stack().is_empty());
+}
+
+#[test]
+fn layer_times_out() {
+    let (mut l, kb, r) = setup();
+    l.register_layer(&"Win".into(), &sv(&["F1"]), Some(0.05))
+        .unwrap();
+
+    kb.press(KeyCode::F1, 0);
+    kb.release(KeyCode::F1, 10);
+    l.poll();
+    assert_eq!(
+        drain(&r),
+        vec![
+            event("Win", ActionEventKind::LayerEntered),
+            event("Win", ActionEventKind::LayerExited)
+        ]
+    );
+    assert!(l.get_layer_stack().is_empty());
+    assert_eq!(l.pop_layer().unwrap(), None);
+}
+
+#[test]
+fn push_and_pop_layers() {
+    let (mut l, _kb, r) = setup();
+    l.register_layer(&"Win".into(), &sv(&["F1"]), None).unwrap();
+
+    l.push_layer(&"Win".into()).unwrap();
+    assert_eq!(drain(&r), vec![event("Win", ActionEventKind::LayerEntered)]);
+    assert_eq!(l.pop_layer().unwrap(), Some("Win".to_string()));
+    assert_eq!(drain(&r), vec![event("Win", ActionEventKind::LayerExited)]);
+    assert!(l.push_layer(&"Nope".into()).is_err());
+}
+
+#[test]
+fn layer_action_payloads() {
+    let (mut l, kb, r) = setup();
+    let (win, split, w) = ("Win".to_string(), "Split".to_string(), sv(&["KeyW"]));
+    l.register_layer(&win, &[], None).unwrap();
+    l.register_layer_action(&win, &split, &w).unwrap();
+    l.set_layer_action_payload(&win, &split, &w, Some(Payload::Boolean(true)))
+        .unwrap();
+    assert_eq!(
+        l.get_layer_action_payload(&win, &split, &w).unwrap(),
+        Some(Payload::Boolean(true))
+    );
+    assert!(matches!(
+        l.get_layer_action_payload(&"Nope".into(), &split, &w),
+        Err(Error::LayerDoesNotExist(_))
+    ));
+    assert!(matches!(
+        l.set_layer_action_payload(&win, &"Other".into(), &w, None),
+        Err(Error::ActionDoesNotExist { .. })
+    ));
+
+    l.push_layer(&win).unwrap();
+    l.poll();
+    kb.press(KeyCode::KeyW, 0);
+    l.poll();
+    let e = r
+        .try_iter()
+        .find(|e| e.action == split)
+        .expect("layer action should fire");
+    assert_eq!(e.payload, Some(Payload::Boolean(true)));
+}
@@ -0,0 +1,189 @@
+mod common;
+
+use common::{drain, event, pressed, setup, sv};
+use hotkey_listener::hotkey_listener::{format_hotkey, parse_hotkey, ActionEventKind, Payload};
+use livesplit_hotkey::KeyCode;
+
+#[test]
+fn sequence_advances_and_resets() {
+    let (mut l, kb, r) = setup();
+    let steps = vec![sv(&["ControlLeft", "KeyK"]), sv(&["ControlLeft", "KeyS"])];
+    l.register_sequence(&"Save".into(), &steps).unwrap();
+    l.register_action(&"Other".into(), &sv(&["KeyQ"])).unwrap();
+    assert_eq!(kb.hooked_keys().len(), 4);
+
+    kb.press(KeyCode::ControlLeft, 0);
+    kb.press(KeyCode::KeyK, 10);
+    kb.press(KeyCode::KeyS, 20);
+    l.poll();
+    assert_eq!(
+        drain(&r),
+        vec![
+            event("Save", ActionEventKind::SequenceAdvanced(1)),
+            pressed("Save")
+        ]
+    );
+
+    // A press that does not continue the sequence resets it
+    kb.press(KeyCode::ControlLeft, 100);
+    kb.press(KeyCode::KeyK, 110);
+    kb.press(KeyCode::KeyQ, 120);
+    kb.press(KeyCode::KeyS, 130);
+    l.poll();
+    assert_eq!(
+        drain(&r),
+        vec![
+            event("Save", ActionEventKind::SequenceAdvanced(1)),
+            event("Save", ActionEventKind::SequenceReset),
+            pressed("Other")
+        ]
+    );
+
+    l.unregister_sequence(&"Save".into(), &steps).unwrap();
+    assert_eq!(kb.hooked_keys(), vec![KeyCode::KeyQ]);
+}
+
+#[test]
+fn sequence_times_out() {
+    let (mut l, kb, r) = setup();
+    let steps = vec![sv(&["KeyK"]), sv(&["KeyS"])];
+    l.register_sequence(&"Save".into(), &steps).unwrap();
+    l.set_sequence_timeout(0.05).unwrap();
+
+    kb.press(KeyCode::KeyK, 0);
+    l.poll();
+    l.poll();
+    assert_eq!(
+        drain(&r),
+        vec![
+            event("Save", ActionEventKind::SequenceAdvanced(1)),
+            event("Save", ActionEventKind::SequenceReset)
+        ]
+    );
+    assert!(l.next_deadline().is_none());
+}
+
+#[test]
+fn queued_steps_expire_by_their_timestamps() {
+    let (mut l, kb, r) = setup();
+    let steps = vec![sv(&["KeyK"]), sv(&["KeyS"])];
+    l.register_sequence(&"Save".into(), &steps).unwrap();
+    l.set_sequence_timeout(0.05).unwrap();
+    l.set_max_events_per_poll(Some(1));
+
+    // Queued in time, so the sequence completes however late it is polled
+    kb.press(KeyCode::KeyK, 0);
+    kb.press(KeyCode::KeyS, 10);
+    l.poll();
+    l.poll();
+    assert_eq!(
+        drain(&r),
+        vec![
+            event("Save", ActionEventKind::SequenceAdvanced(1)),
+            pressed("Save")
+        ]
+    );
+
+    // Queued too late, even though both steps are processed by the same poll
+    l.set_max_events_per_poll(None);
+    kb.press(KeyCode::KeyK, 1000);
+    kb.press(KeyCode::KeyS, 2000);
+    l.poll();
+    assert_eq!(
+        drain(&r),
+        vec![
+            event("Save", ActionEventKind::SequenceAdvanced(1)),
+            event("Save", ActionEventKind::SequenceReset)
+        ]
+    );
+    assert!(l.next_deadline().is_none());
+}
+
+#[test]
+fn sequences_with_modifier_aliases() {
+    let (mut l, kb, r) = setup();
+    let steps = vec![sv(&["Shift", "KeyK"]), sv(&["Shift", "KeyS"])];
+    l.register_sequence(&"S".into(), &steps).unwrap();
+
+    kb.press(KeyCode::ShiftLeft, 0);
+    kb.press(KeyCode::KeyK, 10);
+    kb.press(KeyCode::KeyS, 20);
+    l.poll();
+    assert_eq!(
+        drain(&r),
+        vec![
+            event("S", ActionEventKind::SequenceAdvanced(1)),
+            pressed("S")
+        ]
+    );
+
+    l.unregister_sequence(&"S".into(), &steps).unwrap();
+    assert!(kb.hooked_keys().is_empty());
+}
+
+#[test]
+fn sequence_payloads() {
+    let (mut l, kb, r) = setup();
+    let a = "SetExpression".to_string();
+    let steps = vec![sv(&["KeyK"]), sv(&["Digit3"])];
+    l.register_sequence(&a, &steps).unwrap();
+    l.set_sequence_payload(&a, &steps, Some(Payload::Boolean(true)))
+        .unwrap();
+    assert_eq!(
+        l.get_sequence_payload(&a, &steps).unwrap(),
+        Some(Payload::Boolean(true))
+    );
+
+    kb.press(KeyCode::KeyK, 0);
+    kb.press(KeyCode::Digit3, 10);
+    l.poll();
+    let e = r.try_iter().last().unwrap();
+    assert_eq!(e.kind, ActionEventKind::Pressed);
+    assert_eq!(e.payload, Some(Payload::Boolean(true)));
+}
+
+#[test]
+fn hotkey_strings() {
+    assert_eq!(
+        parse_hotkey("Ctrl+Shift+A").unwrap(),
+        vec![sv(&["Control", "Shift", "KeyA"])]
+    );
+    assert_eq!(
+        parse_hotkey("super + f5").unwrap(),
+        vec![sv(&["Super", "F5"])]
+    );
+    assert_eq!(
+        parse_hotkey("Ctrl+K, Ctrl+S").unwrap(),
+        vec![sv(&["Control", "KeyK"]), sv(&["Control", "KeyS"])]
+    );
+    assert_eq!(
+        parse_hotkey("lctrl+NUM1+arrowup+1").unwrap(),
+        vec![sv(&["ControlLeft", "Numpad1", "ArrowUp", "Digit1"])]
+    );
+    assert!(parse_hotkey("Ctrl+").is_err());
+    assert!(parse_hotkey("Ctrl+Bogus").is_err());
+
+    let steps = vec![
+        sv(&["KeyA", "ShiftLeft", "Control"]),
+        sv(&["Escape", "Digit3"]),
+    ];
+    let s = format_hotkey(&steps).unwrap();
+    assert_eq!(s, "Ctrl+LeftShift+A, 3+Esc");
+    assert_eq!(format_hotkey(&parse_hotkey(&s).unwrap()).unwrap(), s);
+}
+
+#[test]
+fn register_hotkey_strings() {
+    let (mut l, kb, r) = setup();
+    l.register_hotkey(&"X".into(), "Ctrl+X").unwrap();
+    l.register_hotkey(&"Y".into(), "Ctrl+K, Y").unwrap();
+
+    kb.press(KeyCode::ControlLeft, 0);
+    kb.press(KeyCode::KeyX, 10);
+    l.poll();
+    assert_eq!(drain(&r), vec![pressed("X")]);
+
+    l.unregister_hotkey(&"Y".into(), "ctrl+k, y").unwrap();
+    l.unregister_hotkey(&"X".into(), "ctrl+x").unwrap();
+    assert!(kb.hooked_keys().is_empty());
+}
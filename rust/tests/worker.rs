@@ -0,0 +1,73 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{event, pressed, setup, sv};
+use hotkey_listener::hotkey_listener::ActionEventKind;
+use livesplit_hotkey::KeyCode;
+
+#[test]
+fn worker_delivers_events_and_expires_sequences() {
+    let (l, kb, r) = setup();
+    let t = l.spawn();
+    t.listener()
+        .register_sequence(&"S".into(), &[sv(&["KeyA"]), sv(&["KeyB"])])
+        .unwrap();
+    t.listener().set_sequence_timeout(0.05).unwrap();
+    let next = || {
+        let e = r.recv_timeout(Duration::from_secs(1)).unwrap();
+        (e.action, e.kind)
+    };
+
+    kb.backend.press(KeyCode::KeyA);
+    assert_eq!(next(), event("S", ActionEventKind::SequenceAdvanced(1)));
+    assert_eq!(next(), event("S", ActionEventKind::SequenceReset));
+
+    kb.backend.press(KeyCode::KeyA);
+    kb.backend.press(KeyCode::KeyB);
+    assert_eq!(next(), event("S", ActionEventKind::SequenceAdvanced(1)));
+    assert_eq!(next(), pressed("S"));
+    t.shutdown();
+}
+
+#[test]
+fn worker_wakes_up_for_new_deadlines() {
+    let (mut l, _kb, r) = setup();
+    l.register_layer(&"Win".into(), &sv(&["F1"]), Some(0.05))
+        .unwrap();
+    let t = l.spawn();
+
+    // The worker is blocked without a deadline when the layer is pushed
+    t.listener().push_layer(&"Win".into()).unwrap();
+    let next = || {
+        let e = r.recv_timeout(Duration::from_secs(1)).unwrap();
+        (e.action, e.kind)
+    };
+    assert_eq!(next(), event("Win", ActionEventKind::LayerEntered));
+    assert_eq!(next(), event("Win", ActionEventKind::LayerExited));
+    assert!(t.listener().get_layer_stack().is_empty());
+    t.shutdown();
+}
+
+#[test]
+fn worker_expires_sequences_by_event_timestamps() {
+    let (l, kb, r) = setup();
+    let t = l.spawn();
+    t.listener()
+        .register_sequence(&"S".into(), &[sv(&["KeyA"]), sv(&["KeyB"])])
+        .unwrap();
+    t.listener().set_sequence_timeout(0.5).unwrap();
+    let next = || {
+        let e = r.recv_timeout(Duration::from_secs(1)).unwrap();
+        (e.action, e.kind)
+    };
+
+    kb.press(KeyCode::KeyA, 60_000);
+    assert_eq!(next(), event("S", ActionEventKind::SequenceAdvanced(1)));
+
+    // Arrives before the timeout fires, but is dated after it
+    kb.press(KeyCode::KeyB, 61_000);
+    assert_eq!(next(), event("S", ActionEventKind::SequenceReset));
+    assert!(r.recv_timeout(Duration::from_millis(100)).is_err());
+    t.shutdown();
+}